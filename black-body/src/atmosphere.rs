@@ -0,0 +1,154 @@
+//! Transmission of light through the air between an emitter and the eye.
+//!
+//! Distant fireworks look redder and dimmer than the sparks close at hand,
+//! because short wavelengths are scattered out of the line of sight on the way.
+//! [`Atmosphere`] models that loss with Rayleigh scattering by air molecules,
+//! aerosol (haze) extinction following Ångström's law and, optionally, the
+//! absorption bands of water vapor.
+//!
+//! ```rust
+//! use black_body::atmosphere::Atmosphere;
+//! use black_body::spectrum::Spectrum;
+//! use black_body::BlackBody;
+//!
+//! let body = BlackBody::new(2000.0);
+//! let air = Atmosphere::new().with_visibility(5_000.0);
+//! let radiance = |l| body.radiance(l);
+//! let (r, g, b) = Spectrum::to_rgb(&air.attenuate(&radiance, 1_000.0));
+//! println!("color at 1 km: {:?}", (r, g, b));
+//! ```
+use std::f64::consts::PI;
+
+const NANO: f64 = 1.0e-9;
+
+// standard air at sea level (15 °C, 1013.25 hPa)
+const AIR_NUMBER_DENSITY: f64 = 2.547e25; // [1/m^3]
+const AIR_REFRACTIVITY: f64 = 2.7932e-4; // n - 1 at 550 nm
+const KING_FACTOR: f64 = 1.049; // depolarization correction
+
+// aerosol extinction is given at this wavelength
+const REFERENCE_WAVELENGTH: f64 = 550.0; // [nm]
+
+// Koschmieder's constant for a 2 % contrast threshold
+const KOSCHMIEDER: f64 = 3.912;
+
+// water vapor absorption bands in and around the visible range
+// (center [nm], half width [nm], mass absorption coefficient [m^2/kg])
+const WATER_VAPOR_BANDS: [(f64, f64, f64); 5] = [
+    (594.0, 4.0, 0.0006),
+    (651.0, 5.0, 0.0012),
+    (724.0, 9.0, 0.0100),
+    (823.0, 12.0, 0.0250),
+    (942.0, 20.0, 0.0900),
+];
+
+#[derive(Debug, Clone)]
+pub struct Atmosphere {
+    /// scale of molecular (Rayleigh) scattering; 1.0 is sea level air
+    pub air_density_ratio: f64,
+    /// aerosol extinction coefficient at 550 nm [1/m]
+    pub aerosol_extinction: f64,
+    /// Ångström exponent of the aerosol extinction
+    pub angstrom_exponent: f64,
+    /// water vapor density [kg/m^3]; `None` disables the absorption bands
+    pub water_vapor_density: Option<f64>,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Atmosphere {
+    /// Clear sea level air without haze or humidity.
+    pub fn new() -> Self {
+        Self {
+            air_density_ratio: 1.0,
+            aerosol_extinction: 0.0,
+            angstrom_exponent: 1.3,
+            water_vapor_density: None,
+        }
+    }
+
+    /// Sets the aerosol extinction from the meteorological visibility [m].
+    pub fn with_visibility(mut self, visibility: f64) -> Self {
+        assert!(
+            visibility > 0.0,
+            "it requires; visibility > 0\n\
+            visibility must be grater than 0,\n\
+            but got {visibility}"
+        );
+        let total = KOSCHMIEDER / visibility;
+        let molecular = self.rayleigh_coefficient(REFERENCE_WAVELENGTH * NANO);
+        self.aerosol_extinction = (total - molecular).max(0.0);
+        self
+    }
+
+    pub fn with_angstrom_exponent(mut self, exponent: f64) -> Self {
+        self.angstrom_exponent = exponent;
+        self
+    }
+
+    /// Enables water vapor absorption with the given density [kg/m^3].
+    pub fn with_water_vapor(mut self, density: f64) -> Self {
+        assert!(
+            density >= 0.0,
+            "it requires; density >= 0\n\
+            density must be grater than or equal to 0,\n\
+            but got {density}"
+        );
+        self.water_vapor_density = Some(density);
+        self
+    }
+
+    pub fn rayleigh_coefficient(&self, wavelength: f64) -> f64 {
+        // ref: https://en.wikipedia.org/wiki/Rayleigh_scattering
+        let l = wavelength;
+        let n2 = (1.0 + AIR_REFRACTIVITY).powi(2) - 1.0;
+        let sigma = 8.0 * PI.powi(3) * n2.powi(2) / (3.0 * AIR_NUMBER_DENSITY.powi(2) * l.powi(4));
+        sigma * KING_FACTOR * AIR_NUMBER_DENSITY * self.air_density_ratio
+    }
+
+    pub fn aerosol_coefficient(&self, wavelength: f64) -> f64 {
+        // Ångström's law: β(λ) = β(λ0) (λ / λ0)^-α
+        // ref: https://en.wikipedia.org/wiki/Angstrom_exponent
+        let ratio = wavelength / (REFERENCE_WAVELENGTH * NANO);
+        self.aerosol_extinction * ratio.powf(-self.angstrom_exponent)
+    }
+
+    pub fn water_vapor_coefficient(&self, wavelength: f64) -> f64 {
+        let Some(density) = self.water_vapor_density else {
+            return 0.0;
+        };
+        let l = wavelength / NANO;
+        WATER_VAPOR_BANDS
+            .iter()
+            .map(|(center, width, k)| k * (-((l - center) / width).powi(2)).exp())
+            .sum::<f64>()
+            * density
+    }
+
+    /// Total extinction coefficient [1/m] at the wavelength [m].
+    pub fn extinction(&self, wavelength: f64) -> f64 {
+        self.rayleigh_coefficient(wavelength)
+            + self.aerosol_coefficient(wavelength)
+            + self.water_vapor_coefficient(wavelength)
+    }
+
+    /// Fraction of light at the wavelength [m] that survives the path [m].
+    pub fn transmittance(&self, wavelength: f64, path_length: f64) -> f64 {
+        // Beer–Lambert law
+        // ref: https://en.wikipedia.org/wiki/Beer%E2%80%93Lambert_law
+        (-self.extinction(wavelength) * path_length).exp()
+    }
+
+    /// Wraps a spectrum so that it is seen through `path_length` [m] of air.
+    pub fn attenuate<'a>(
+        &'a self,
+        radiance: &'a dyn Fn(f64) -> f64,
+        path_length: f64,
+    ) -> impl Fn(f64) -> f64 + 'a {
+        move |l| radiance(l) * self.transmittance(l, path_length)
+    }
+}
@@ -1,9 +1,15 @@
 use plotters::prelude::*;
 
+use black_body::cvd::{Cvd, Deficiency};
 use black_body::BlackBody;
 
+// usage: color_temperature [--cvd protanopia|deuteranopia|tritanopia]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let output_path = "artifacts/output/color_temperature.png";
+    let cvd = parse_cvd()?;
+    let output_path = match cvd {
+        Some(cvd) => format!("artifacts/output/color_temperature_{}.png", cvd.deficiency),
+        None => "artifacts/output/color_temperature.png".to_string(),
+    };
     let width = 1080;
     let height = 720;
 
@@ -11,7 +17,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (y_min, y_max) = (0.0, 1.0 as f32);
     let (x_lim, y_lim) = (x_min..x_max, y_min..y_max);
 
-    let root = BitMapBackend::new(output_path, (width, height)).into_drawing_area();
+    let root = BitMapBackend::new(&output_path, (width, height)).into_drawing_area();
     root.fill(&WHITE)?;
     let mut chart = ChartBuilder::on(&root)
         .caption("Color temperature", ("sans-serif", 30))
@@ -43,6 +49,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         let body = BlackBody::new(x as f64);
         let color = body.color_for_eye();
+        let color = cvd.map_or(color, |cvd| cvd.simulate(color));
 
         for yi in 0..=pixel_heght {
             let y = yi as f32 / pixel_heght as f32;
@@ -62,3 +69,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+fn parse_cvd() -> Result<Option<Cvd>, String> {
+    let args = std::env::args().collect::<Vec<_>>();
+    match args.iter().position(|a| a == "--cvd") {
+        Some(i) => {
+            let name = args.get(i + 1).ok_or("--cvd requires a deficiency")?;
+            let deficiency = name.parse::<Deficiency>()?;
+            Ok(Some(Cvd::new(deficiency)))
+        }
+        None => Ok(None),
+    }
+}
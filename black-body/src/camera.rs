@@ -0,0 +1,283 @@
+//! A simple digital camera, to compare rendered sparks with photos and video.
+//!
+//! [`CameraModel`] integrates a spectrum against the red, green and blue
+//! sensitivities of a sensor, scales the signal by exposure time and ISO gain,
+//! clips it at the sensor saturation, and then white balances and gamma
+//! encodes it the way a camera pipeline does. The clipping is what turns the
+//! hot core of a spark into a blown-out white blob on a phone video.
+//!
+//! ```rust
+//! use black_body::camera::{CameraModel, SpectralSensitivity};
+//! use black_body::BlackBody;
+//!
+//! let camera = CameraModel::new(SpectralSensitivity::smartphone())
+//!     .with_exposure_time(1.0 / 60.0)
+//!     .with_iso(400.0);
+//! let spark = BlackBody::new(1500.0);
+//! // the red channel of the core blows out at ISO 400
+//! assert_eq!(camera.raw(&|l| spark.radiance(l))[0], 1.0);
+//! let rgb = camera.processed(&|l| spark.radiance(l));
+//! println!("spark on camera: {:?}", rgb);
+//! ```
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::spectrum::ColorFunction;
+use crate::BlackBody;
+
+// sensor wave length range; sensors without an IR cut filter see beyond 780 nm
+const SENSOR_LOWER: f64 = 380.0; // [nm]
+const SENSOR_UPPER: f64 = 1000.0; // [nm]
+const WAVE_LENGTH_STEP: f64 = 1.0; // [nm]
+const NANO: f64 = 1.0e-9;
+
+const BASE_ISO: f64 = 100.0;
+// raw signal per unit of weighted radiance [(W sr^-1 m^-2 s)^-1] at base ISO,
+// chosen so a 1500 K spark fills half of the red channel at 1/60 s
+const DEFAULT_RESPONSIVITY: f64 = 2.0;
+
+// white balance presets [K]
+pub const TUNGSTEN: f64 = 3200.0;
+pub const FLUORESCENT: f64 = 4000.0;
+pub const DAYLIGHT: f64 = 5500.0;
+pub const CLOUDY: f64 = 6500.0;
+pub const SHADE: f64 = 7500.0;
+
+/// Tabulated red, green and blue sensitivities of a sensor.
+#[derive(Debug, Clone)]
+pub struct SpectralSensitivity {
+    /// (wave length [nm], [r, g, b]) sorted by wave length
+    samples: Vec<(f64, [f64; 3])>,
+}
+
+impl SpectralSensitivity {
+    pub fn new(mut samples: Vec<(f64, [f64; 3])>) -> Self {
+        assert!(
+            samples.len() >= 2,
+            "it requires; samples.len() >= 2\n\
+            sensitivity needs at least 2 samples,\n\
+            but got {}",
+            samples.len()
+        );
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { samples }
+    }
+
+    /// Typical phone sensor behind an IR cut filter.
+    pub fn smartphone() -> Self {
+        Self::from_gaussians(
+            [
+                (600.0, 38.0, 32.0),
+                (535.0, 40.0, 45.0),
+                (460.0, 28.0, 40.0),
+            ],
+            [1.0, 1.0, 0.9],
+            Some(680.0),
+        )
+    }
+
+    /// Interchangeable lens camera with a sharper filter array.
+    pub fn dslr() -> Self {
+        Self::from_gaussians(
+            [
+                (605.0, 30.0, 28.0),
+                (530.0, 35.0, 40.0),
+                (455.0, 25.0, 35.0),
+            ],
+            [1.0, 1.0, 1.0],
+            Some(690.0),
+        )
+    }
+
+    /// Cheap webcam without an IR cut filter, so near infrared leaks in.
+    pub fn webcam() -> Self {
+        Self::from_gaussians(
+            [
+                (610.0, 40.0, 120.0),
+                (535.0, 45.0, 90.0),
+                (460.0, 30.0, 80.0),
+            ],
+            [1.0, 0.8, 0.7],
+            None,
+        )
+    }
+
+    /// Parses lines of `wave length [nm], r, g, b`.
+    ///
+    /// Empty lines and `#` comments are skipped, and so is a header line if
+    /// it comes first; any other non-numeric line is an error.
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut samples = vec![];
+        let mut first = true;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let header = std::mem::replace(&mut first, false);
+            let fields = line
+                .split(',')
+                .map(|f| f.trim().parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+            match fields {
+                Ok(f) if f.len() == 4 => samples.push((f[0], [f[1], f[2], f[3]])),
+                Ok(f) => {
+                    return Err(format!(
+                        "line {}: expected 4 columns, but got {}",
+                        i + 1,
+                        f.len()
+                    ))
+                }
+                Err(_) if header => continue,
+                Err(e) => return Err(format!("line {}: {e}", i + 1)),
+            }
+        }
+        if samples.len() < 2 {
+            return Err(format!(
+                "expected at least 2 samples, but got {}",
+                samples.len()
+            ));
+        }
+        Ok(Self::new(samples))
+    }
+
+    pub fn load_csv<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        Ok(Self::from_csv(&text)?)
+    }
+
+    /// Sensitivities at the wave length [nm], linearly interpolated, and 0
+    /// outside the samples; a curve measured up to 700 nm says nothing of the
+    /// infrared beyond.
+    pub fn at(&self, wavelength: f64) -> [f64; 3] {
+        let l = wavelength;
+        let first = self.samples[0];
+        let last = self.samples[self.samples.len() - 1];
+        if l < first.0 || l > last.0 {
+            return [0.0; 3];
+        }
+        if l == last.0 {
+            return last.1;
+        }
+        let i = self.samples.partition_point(|s| s.0 <= l);
+        let (l0, s0) = self.samples[i - 1];
+        let (l1, s1) = self.samples[i];
+        let t = (l - l0) / (l1 - l0);
+        [
+            s0[0] + (s1[0] - s0[0]) * t,
+            s0[1] + (s1[1] - s0[1]) * t,
+            s0[2] + (s1[2] - s0[2]) * t,
+        ]
+    }
+
+    fn from_gaussians(peaks: [(f64, f64, f64); 3], gains: [f64; 3], ir_cut: Option<f64>) -> Self {
+        let steps = ((SENSOR_UPPER - SENSOR_LOWER) / WAVE_LENGTH_STEP) as usize;
+        let samples = (0..=steps)
+            .map(|i| {
+                let l = SENSOR_LOWER + i as f64 * WAVE_LENGTH_STEP;
+                // the IR cut filter rolls off over about 30 nm
+                let filter = ir_cut.map_or(1.0, |cut| 1.0 / (1.0 + ((l - cut) / 8.0).exp()));
+                let s = |c: usize| {
+                    let (mu, sigma1, sigma2) = peaks[c];
+                    gains[c] * ColorFunction::segmented_gaussian(l, mu, sigma1, sigma2) * filter
+                };
+                (l, [s(0), s(1), s(2)])
+            })
+            .collect();
+        Self::new(samples)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CameraModel {
+    pub sensitivity: SpectralSensitivity,
+    /// [s]
+    pub exposure_time: f64,
+    pub iso: f64,
+    /// color temperature rendered as neutral gray [K]
+    pub white_balance: f64,
+    /// raw signal at which a pixel saturates
+    pub saturation: f64,
+    /// raw signal per unit of weighted radiance and second at base ISO
+    pub responsivity: f64,
+}
+
+impl CameraModel {
+    pub fn new(sensitivity: SpectralSensitivity) -> Self {
+        Self {
+            sensitivity,
+            exposure_time: 1.0 / 60.0,
+            iso: BASE_ISO,
+            white_balance: DAYLIGHT,
+            saturation: 1.0,
+            responsivity: DEFAULT_RESPONSIVITY,
+        }
+    }
+
+    pub fn with_exposure_time(mut self, exposure_time: f64) -> Self {
+        self.exposure_time = exposure_time;
+        self
+    }
+
+    pub fn with_iso(mut self, iso: f64) -> Self {
+        self.iso = iso;
+        self
+    }
+
+    pub fn with_white_balance(mut self, kelvin: f64) -> Self {
+        self.white_balance = kelvin;
+        self
+    }
+
+    pub fn with_saturation(mut self, saturation: f64) -> Self {
+        self.saturation = saturation;
+        self
+    }
+
+    /// Linear sensor signal in [0, 1], clipped at saturation per channel.
+    pub fn raw(&self, radiance: &dyn Fn(f64) -> f64) -> [f64; 3] {
+        let gain = self.responsivity * self.exposure_time * self.iso / BASE_ISO;
+        let signal = self.integrate(radiance);
+        signal.map(|s| (s * gain).clamp(0.0, self.saturation) / self.saturation)
+    }
+
+    /// White balanced, gamma encoded sRGB in [0, 1].
+    pub fn processed(&self, radiance: &dyn Fn(f64) -> f64) -> [f64; 3] {
+        let raw = self.raw(radiance);
+        let gains = self.white_balance_gains();
+        [
+            srgb_gamma((raw[0] * gains[0]).min(1.0)),
+            srgb_gamma((raw[1] * gains[1]).min(1.0)),
+            srgb_gamma((raw[2] * gains[2]).min(1.0)),
+        ]
+    }
+
+    /// Channel gains that render a black body at `white_balance` as gray.
+    pub fn white_balance_gains(&self) -> [f64; 3] {
+        let body = BlackBody::new(self.white_balance);
+        let [r, g, b] = self.integrate(&|l| body.radiance(l));
+        [g / r, 1.0, g / b]
+    }
+
+    fn integrate(&self, radiance: &dyn Fn(f64) -> f64) -> [f64; 3] {
+        let steps = ((SENSOR_UPPER - SENSOR_LOWER) / WAVE_LENGTH_STEP) as usize;
+        let dl = WAVE_LENGTH_STEP * NANO;
+        (0..steps)
+            .map(|i| SENSOR_LOWER + i as f64 * WAVE_LENGTH_STEP)
+            .fold([0.0; 3], |acc, l| {
+                let e = radiance(l * NANO) * dl;
+                let s = self.sensitivity.at(l);
+                [acc[0] + e * s[0], acc[1] + e * s[1], acc[2] + e * s[2]]
+            })
+    }
+}
+
+fn srgb_gamma(linear: f64) -> f64 {
+    // ref: https://en.wikipedia.org/wiki/SRGB
+    if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
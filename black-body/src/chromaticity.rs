@@ -0,0 +1,174 @@
+//! Chromaticity utilities: the spectral locus, dominant wavelength and
+//! excitation purity.
+//!
+//! Pyrotechnic star colors are described by their dominant wavelength (the
+//! monochromatic hue they look like) and purity (how far they are from white
+//! towards that hue). Colors on the purple side of the white point have no
+//! dominant wavelength; they get a complementary wavelength instead.
+//!
+//! ```rust
+//! use black_body::chromaticity::{SpectralLocus, EQUAL_ENERGY};
+//!
+//! let locus = SpectralLocus::new();
+//! let xy = [0.55, 0.40];
+//! println!("hue: {:?}", locus.dominant_wavelength(xy, EQUAL_ENERGY));
+//! println!("purity: {:?}", locus.excitation_purity(xy, EQUAL_ENERGY));
+//! ```
+use crate::spectrum::ColorFunction;
+
+// visible light wave length range
+const VISIBLE_LOWER: f64 = 380.0; // [nm]
+const VISIBLE_UPPER: f64 = 780.0; // [nm]
+const WAVE_LENGTH_STEP: f64 = 1.0; // [nm]
+
+// white points in CIE 1931 xy
+pub const EQUAL_ENERGY: [f64; 2] = [1.0 / 3.0, 1.0 / 3.0];
+pub const D65: [f64; 2] = [0.31271, 0.32902];
+pub const ILLUMINANT_A: [f64; 2] = [0.44757, 0.40745];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hue {
+    /// wave length [nm] whose monochromatic color lies beyond the sample
+    Dominant(f64),
+    /// wave length [nm] opposite to the sample, for purples
+    Complementary(f64),
+}
+
+impl Hue {
+    pub fn wavelength(&self) -> f64 {
+        match self {
+            Self::Dominant(l) | Self::Complementary(l) => *l,
+        }
+    }
+}
+
+pub fn xyz_to_xy(x: f64, y: f64, z: f64) -> [f64; 2] {
+    let sum = x + y + z;
+    [x / sum, y / sum]
+}
+
+/// Chromaticities of monochromatic light, closed by the line of purples.
+#[derive(Debug, Clone)]
+pub struct SpectralLocus {
+    /// (wave length [nm], xy) from violet to red
+    points: Vec<(f64, [f64; 2])>,
+}
+
+impl Default for SpectralLocus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectralLocus {
+    /// Samples the locus from the `ColorFunction` fit.
+    ///
+    /// The fit folds back on itself in the far violet and the far red, where
+    /// the functions are nearly zero, so the locus is trimmed to the most
+    /// violet (lowest y) and the most red (highest x) points.
+    pub fn new() -> Self {
+        let steps = ((VISIBLE_UPPER - VISIBLE_LOWER) / WAVE_LENGTH_STEP) as usize;
+        let points = (0..=steps)
+            .map(|i| {
+                let l = VISIBLE_LOWER + i as f64 * WAVE_LENGTH_STEP;
+                (l, Self::chromaticity(l))
+            })
+            .collect::<Vec<_>>();
+
+        let peak = |key: &dyn Fn(&[f64; 2]) -> f64| {
+            (0..points.len())
+                .max_by(|&a, &b| key(&points[a].1).total_cmp(&key(&points[b].1)))
+                .unwrap()
+        };
+        let first = peak(&|xy| -xy[1]);
+        let last = peak(&|xy| xy[0]);
+
+        Self {
+            points: points[first..=last].to_vec(),
+        }
+    }
+
+    /// xy of monochromatic light at the wave length [nm].
+    pub fn chromaticity(wavelength: f64) -> [f64; 2] {
+        let l = wavelength;
+        xyz_to_xy(
+            ColorFunction::x(l),
+            ColorFunction::y(l),
+            ColorFunction::z(l),
+        )
+    }
+
+    /// Wave length range [nm] covered by the locus.
+    pub fn range(&self) -> (f64, f64) {
+        (self.points[0].0, self.points[self.points.len() - 1].0)
+    }
+
+    pub fn dominant_wavelength(&self, xy: [f64; 2], white: [f64; 2]) -> Option<Hue> {
+        let direction = [xy[0] - white[0], xy[1] - white[1]];
+        match self.intersect_locus(white, direction) {
+            Some((l, _)) => Some(Hue::Dominant(l)),
+            None => {
+                let opposite = [-direction[0], -direction[1]];
+                self.intersect_locus(white, opposite)
+                    .map(|(l, _)| Hue::Complementary(l))
+            }
+        }
+    }
+
+    /// Distance from white to the sample over the distance from white to the
+    /// boundary (the locus, or the line of purples) in the same direction.
+    pub fn excitation_purity(&self, xy: [f64; 2], white: [f64; 2]) -> Option<f64> {
+        let direction = [xy[0] - white[0], xy[1] - white[1]];
+        if direction == [0.0, 0.0] {
+            return Some(0.0);
+        }
+        let t = match self.intersect_locus(white, direction) {
+            Some((_, t)) => t,
+            None => self.intersect_purples(white, direction)?,
+        };
+        // the sample is at t = 1 along the direction
+        Some(1.0 / t)
+    }
+
+    /// Nearest hit of the ray `origin + t direction` (t > 0) with the locus,
+    /// as (wave length, t).
+    fn intersect_locus(&self, origin: [f64; 2], direction: [f64; 2]) -> Option<(f64, f64)> {
+        self.points
+            .windows(2)
+            .filter_map(|w| {
+                let ((l0, a), (l1, b)) = (w[0], w[1]);
+                let (t, s) = intersect(origin, direction, a, b)?;
+                Some((l0 + (l1 - l0) * s, t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    fn intersect_purples(&self, origin: [f64; 2], direction: [f64; 2]) -> Option<f64> {
+        let a = self.points[self.points.len() - 1].1;
+        let b = self.points[0].1;
+        intersect(origin, direction, a, b).map(|(t, _)| t)
+    }
+}
+
+/// Intersection of the ray `origin + t direction` (t > 0) with the segment
+/// `a + s (b - a)` (0 <= s <= 1), as (t, s).
+fn intersect(
+    origin: [f64; 2],
+    direction: [f64; 2],
+    a: [f64; 2],
+    b: [f64; 2],
+) -> Option<(f64, f64)> {
+    let edge = [b[0] - a[0], b[1] - a[1]];
+    let denominator = direction[0] * edge[1] - direction[1] * edge[0];
+    if denominator.abs() < f64::EPSILON {
+        return None;
+    }
+    let offset = [a[0] - origin[0], a[1] - origin[1]];
+    let t = (offset[0] * edge[1] - offset[1] * edge[0]) / denominator;
+    let s = (offset[0] * direction[1] - offset[1] * direction[0]) / denominator;
+    if t > 0.0 && (0.0..=1.0).contains(&s) {
+        Some((t, s))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,58 @@
+//! Conversions between wavelength, frequency and wavenumber, and between the
+//! spectral densities expressed per unit of each.
+//!
+//! All quantities are in SI: wavelength [m], frequency [Hz], wavenumber [1/m].
+//! A density per unit wavelength `f_λ` relates to the other forms by
+//! `f_λ |dλ| = f_ν |dν| = f_ν̃ |dν̃|`.
+use crate::{C, H};
+
+pub fn wavelength_to_frequency(wavelength: f64) -> f64 {
+    C / wavelength
+}
+
+pub fn frequency_to_wavelength(frequency: f64) -> f64 {
+    C / frequency
+}
+
+pub fn wavelength_to_wavenumber(wavelength: f64) -> f64 {
+    1.0 / wavelength
+}
+
+pub fn wavenumber_to_wavelength(wavenumber: f64) -> f64 {
+    1.0 / wavenumber
+}
+
+/// Energy of a single photon [J] at the wavelength [m].
+pub fn photon_energy(wavelength: f64) -> f64 {
+    H * C / wavelength
+}
+
+/// `f_λ` at `wavelength` to `f_ν`, using |dλ/dν| = λ² / c.
+pub fn per_wavelength_to_per_frequency(value: f64, wavelength: f64) -> f64 {
+    value * wavelength.powi(2) / C
+}
+
+/// `f_ν` at `frequency` to `f_λ`, using |dν/dλ| = ν² / c.
+pub fn per_frequency_to_per_wavelength(value: f64, frequency: f64) -> f64 {
+    value * frequency.powi(2) / C
+}
+
+/// `f_λ` at `wavelength` to `f_ν̃`, using |dλ/dν̃| = λ².
+pub fn per_wavelength_to_per_wavenumber(value: f64, wavelength: f64) -> f64 {
+    value * wavelength.powi(2)
+}
+
+/// `f_ν̃` at `wavenumber` to `f_λ`, using |dν̃/dλ| = ν̃².
+pub fn per_wavenumber_to_per_wavelength(value: f64, wavenumber: f64) -> f64 {
+    value * wavenumber.powi(2)
+}
+
+/// Energy radiance to photon radiance at the wavelength [m].
+pub fn energy_to_photons(value: f64, wavelength: f64) -> f64 {
+    value / photon_energy(wavelength)
+}
+
+/// Photon radiance to energy radiance at the wavelength [m].
+pub fn photons_to_energy(value: f64, wavelength: f64) -> f64 {
+    value * photon_energy(wavelength)
+}
@@ -0,0 +1,257 @@
+//! Color vision deficiency simulation.
+//!
+//! The red-orange-yellow gradient of a black body is exactly the range that
+//! collapses for protanopes and deuteranopes. [`Cvd`] maps a linear RGB color
+//! to how it appears with one cone type missing (protanopia, deuteranopia,
+//! tritanopia), with a choice of the classic simulation methods:
+//!
+//! - Brettel, Viénot and Mollon (1997): projection onto two half planes in
+//!   LMS space, anchored at monochromatic colors seen alike by both observers.
+//! - Viénot, Brettel and Mollon (1999): a single plane, which is a good fit
+//!   for protanopia and deuteranopia. Tritanopia falls back to Brettel.
+//! - Machado, Oliveira and Fernandes (2009): one matrix per deficiency, which
+//!   also models anomalous trichromacy through its severity.
+//!
+//! ```rust
+//! use black_body::cvd::{Cvd, Deficiency, Method};
+//!
+//! let cvd = Cvd::new(Deficiency::Deuteranopia).with_method(Method::Vienot);
+//! println!("orange for a deuteranope: {:?}", cvd.simulate([1.0, 0.5, 0.0]));
+//! ```
+use std::fmt;
+use std::str::FromStr;
+
+use crate::spectrum::ColorFunction;
+
+// linear sRGB to LMS (Viénot et al. 1999)
+const RGB_TO_LMS: [[f64; 3]; 3] = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+const LMS_TO_RGB: [[f64; 3]; 3] = [
+    [0.0809444479, -0.130504409, 0.116721066],
+    [-0.0102485335, 0.0540193266, -0.113614708],
+    [-0.000365296938, -0.00412161469, 0.693511405],
+];
+// CIE XYZ to linear sRGB (D65)
+const XYZ_TO_RGB: [[f64; 3]; 3] = [
+    [3.2406, -1.5372, -0.4986],
+    [-0.9689, 1.8758, 0.0415],
+    [0.0557, -0.2040, 1.0570],
+];
+
+// Machado et al. (2009), severity 1.0
+// ref: https://www.inf.ufrgs.br/~oliveira/pubs_files/CVD_Simulation/CVD_Simulation.html
+const MACHADO_PROTANOPIA: [[f64; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const MACHADO_DEUTERANOPIA: [[f64; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const MACHADO_TRITANOPIA: [[f64; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Deficiency {
+    /// Index of the missing cone in LMS.
+    fn cone(&self) -> usize {
+        match self {
+            Self::Protanopia => 0,
+            Self::Deuteranopia => 1,
+            Self::Tritanopia => 2,
+        }
+    }
+
+    /// Wave lengths [nm] seen identically by normal and dichromatic observers.
+    fn anchors(&self) -> (f64, f64) {
+        match self {
+            Self::Protanopia | Self::Deuteranopia => (475.0, 575.0),
+            Self::Tritanopia => (485.0, 660.0),
+        }
+    }
+}
+
+impl fmt::Display for Deficiency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Deficiency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "protanopia" | "protan" => Ok(Self::Protanopia),
+            "deuteranopia" | "deutan" => Ok(Self::Deuteranopia),
+            "tritanopia" | "tritan" => Ok(Self::Tritanopia),
+            _ => Err(format!(
+                "expected protanopia, deuteranopia or tritanopia, but got {s}"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    Brettel,
+    Vienot,
+    /// `severity` in [0, 1]; 1 is dichromacy, below is anomalous trichromacy
+    Machado {
+        severity: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cvd {
+    pub deficiency: Deficiency,
+    pub method: Method,
+}
+
+impl Cvd {
+    pub fn new(deficiency: Deficiency) -> Self {
+        Self {
+            deficiency,
+            method: Method::Brettel,
+        }
+    }
+
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Simulates how the linear RGB color is seen.
+    pub fn simulate(&self, rgb: [f64; 3]) -> [f64; 3] {
+        match self.matrix() {
+            Some(m) => mul(&m, rgb),
+            None => self.brettel(rgb),
+        }
+    }
+
+    /// The simulation as a linear RGB matrix, if the method is linear.
+    ///
+    /// Brettel's two half planes are not linear, so it returns `None`.
+    pub fn matrix(&self) -> Option<[[f64; 3]; 3]> {
+        match (self.method, self.deficiency) {
+            (Method::Brettel, _) | (Method::Vienot, Deficiency::Tritanopia) => None,
+            (Method::Vienot, deficiency) => {
+                let projection = projection(deficiency.cone(), &vienot_normal());
+                Some(matmul(&LMS_TO_RGB, &matmul(&projection, &RGB_TO_LMS)))
+            }
+            (Method::Machado { severity }, deficiency) => {
+                // linear interpolation from the identity approximates the
+                // intermediate severities tabulated in the paper
+                let full = match deficiency {
+                    Deficiency::Protanopia => MACHADO_PROTANOPIA,
+                    Deficiency::Deuteranopia => MACHADO_DEUTERANOPIA,
+                    Deficiency::Tritanopia => MACHADO_TRITANOPIA,
+                };
+                let s = severity.clamp(0.0, 1.0);
+                let mut m = [[0.0; 3]; 3];
+                for i in 0..3 {
+                    for j in 0..3 {
+                        let identity = if i == j { 1.0 } else { 0.0 };
+                        m[i][j] = identity + (full[i][j] - identity) * s;
+                    }
+                }
+                Some(m)
+            }
+        }
+    }
+
+    fn brettel(&self, rgb: [f64; 3]) -> [f64; 3] {
+        let d = self.deficiency.cone();
+        let (a1, a2) = self.deficiency.anchors();
+        let white = mul(&RGB_TO_LMS, [1.0, 1.0, 1.0]);
+        let anchor1 = monochromatic_lms(a1);
+        let anchor2 = monochromatic_lms(a2);
+
+        // the plane through white and the missing cone axis separates the
+        // colors that project onto each half plane
+        let mut axis = [0.0; 3];
+        axis[d] = 1.0;
+        let separation = cross(white, axis);
+
+        let lms = mul(&RGB_TO_LMS, rgb);
+        let anchor = if dot(separation, lms).signum() == dot(separation, anchor1).signum() {
+            anchor1
+        } else {
+            anchor2
+        };
+        let normal = cross(white, anchor);
+        mul(&LMS_TO_RGB, mul(&projection(d, &normal), lms))
+    }
+}
+
+fn vienot_normal() -> [f64; 3] {
+    // a single plane through white and the blue primary
+    let white = mul(&RGB_TO_LMS, [1.0, 1.0, 1.0]);
+    let blue = mul(&RGB_TO_LMS, [0.0, 0.0, 1.0]);
+    cross(white, blue)
+}
+
+/// LMS of a monochromatic color at the wave length [nm].
+fn monochromatic_lms(wavelength: f64) -> [f64; 3] {
+    let xyz = [
+        ColorFunction::x(wavelength),
+        ColorFunction::y(wavelength),
+        ColorFunction::z(wavelength),
+    ];
+    mul(&RGB_TO_LMS, mul(&XYZ_TO_RGB, xyz))
+}
+
+/// Matrix replacing the cone `d` so that the LMS lies on the plane `normal`.
+fn projection(d: usize, normal: &[f64; 3]) -> [[f64; 3]; 3] {
+    let mut m = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    m[d] = [0.0; 3];
+    for j in (0..3).filter(|&j| j != d) {
+        m[d][j] = -normal[j] / normal[d];
+    }
+    m
+}
+
+fn mul(m: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    [dot(m[0], v), dot(m[1], v), dot(m[2], v)]
+}
+
+fn matmul(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut m = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i][j] = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
@@ -21,7 +21,13 @@
 //! println!("body radiance for wave length: {:?}", body.radiance(1.0e+3));
 //! println!("body color: {:?}", body.color_for_eye());
 //! ```
+pub mod atmosphere;
+pub mod camera;
+pub mod chromaticity;
+pub mod conversion;
+pub mod cvd;
 pub mod spectrum;
+use atmosphere::Atmosphere;
 use spectrum::Spectrum;
 
 // physical constants
@@ -50,14 +56,56 @@ impl BlackBody {
         // ref: https://en.wikipedia.org/wiki/Planck%27s_law
         let l = wavelength;
         let t = self.temperature;
-        let first = 2.0 * H * C.powf(2.0) / l.powf(5.0);
-        let second = 1.0 / (((H * C) / (l * K * t)).exp() - 1.0);
+        let first = 2.0 * H * C.powi(2) / l.powi(5);
+        // libm gives the same bits on every target, which keeps simulations
+        // that color sparks by temperature reproducible
+        let second = 1.0 / (libm::exp((H * C) / (l * K * t)) - 1.0);
         return first * second;
     }
 
+    /// Spectral radiance per unit frequency [W sr^-1 m^-2 Hz^-1].
+    pub fn radiance_per_frequency(&self, frequency: f64) -> f64 {
+        let v = frequency;
+        let t = self.temperature;
+        let first = 2.0 * H * v.powi(3) / C.powi(2);
+        let second = 1.0 / (((H * v) / (K * t)).exp() - 1.0);
+        first * second
+    }
+
+    /// Spectral radiance per unit wavenumber [W sr^-1 m^-2 m].
+    pub fn radiance_per_wavenumber(&self, wavenumber: f64) -> f64 {
+        let n = wavenumber;
+        let t = self.temperature;
+        let first = 2.0 * H * C.powi(2) * n.powi(3);
+        let second = 1.0 / (((H * C * n) / (K * t)).exp() - 1.0);
+        first * second
+    }
+
+    /// Photon spectral radiance per unit wavelength [photons s^-1 sr^-1 m^-2 m^-1].
+    pub fn photon_radiance(&self, wavelength: f64) -> f64 {
+        conversion::energy_to_photons(self.radiance(wavelength), wavelength)
+    }
+
+    /// Photon spectral radiance per unit frequency [photons s^-1 sr^-1 m^-2 Hz^-1].
+    pub fn photon_radiance_per_frequency(&self, frequency: f64) -> f64 {
+        self.radiance_per_frequency(frequency) / (H * frequency)
+    }
+
     pub fn color_for_eye(&self) -> [f64; 3] {
         let (r, g, b) = Spectrum::to_rgb(&|l| self.radiance(l));
         let mean = (r + g + b) / 3.0;
         [r / mean, g / mean, b / mean]
     }
+
+    /// Color seen through `distance` [m] of air.
+    ///
+    /// Normalized like `color_for_eye` before attenuation, so the result is
+    /// both redder and dimmer than the color of the body up close.
+    pub fn color_through(&self, atmosphere: &Atmosphere, distance: f64) -> [f64; 3] {
+        let radiance = |l| self.radiance(l);
+        let (r0, g0, b0) = Spectrum::to_rgb(&radiance);
+        let mean = (r0 + g0 + b0) / 3.0;
+        let (r, g, b) = Spectrum::to_rgb(&atmosphere.attenuate(&radiance, distance));
+        [r / mean, g / mean, b / mean]
+    }
 }
@@ -88,11 +88,11 @@ impl ColorFunction {
         .sum()
     }
 
-    fn segmented_gaussian(x: f64, mu: f64, sigma1: f64, sigma2: f64) -> f64 {
+    pub(crate) fn segmented_gaussian(x: f64, mu: f64, sigma1: f64, sigma2: f64) -> f64 {
         if x < mu {
-            (-(x - mu).powf(2.0) / (2.0 * sigma1.powf(2.0))).exp()
+            libm::exp(-(x - mu).powi(2) / (2.0 * sigma1.powi(2)))
         } else {
-            (-(x - mu).powf(2.0) / (2.0 * sigma2.powf(2.0))).exp()
+            libm::exp(-(x - mu).powi(2) / (2.0 * sigma2.powi(2)))
         }
     }
 }
@@ -0,0 +1,77 @@
+use black_body::atmosphere::Atmosphere;
+
+const NANO: f64 = 1.0e-9;
+
+// wave lengths [nm] across the visible range and the near infrared
+fn wavelengths() -> impl Iterator<Item = f64> {
+    (380..=1000).step_by(5).map(|l| l as f64)
+}
+
+#[test]
+fn nothing_is_lost_over_no_distance() {
+    let hazy = Atmosphere::new()
+        .with_visibility(2_000.0)
+        .with_water_vapor(0.01);
+    for air in [Atmosphere::new(), hazy] {
+        for l in wavelengths() {
+            assert_eq!(air.transmittance(l * NANO, 0.0), 1.0);
+        }
+    }
+}
+
+#[test]
+fn transmittance_falls_with_distance() {
+    let air = Atmosphere::new()
+        .with_visibility(10_000.0)
+        .with_water_vapor(0.01);
+    for l in wavelengths() {
+        let mut last = 1.0;
+        for distance in [10.0, 100.0, 1_000.0, 5_000.0, 20_000.0] {
+            let t = air.transmittance(l * NANO, distance);
+            assert!(t < last, "{t} at {distance} m after {last}, at {l} nm");
+            assert!(t > 0.0);
+            last = t;
+        }
+    }
+}
+
+#[test]
+fn rayleigh_scattering_takes_blue_before_red() {
+    let air = Atmosphere::new();
+    let (blue, red) = (450.0 * NANO, 650.0 * NANO);
+    // λ^-4
+    let ratio = air.rayleigh_coefficient(blue) / air.rayleigh_coefficient(red);
+    assert!((ratio - (650.0f64 / 450.0).powi(4)).abs() < 1.0e-9);
+
+    let distance = 20_000.0;
+    assert!(air.transmittance(blue, distance) < air.transmittance(red, distance));
+    let color = black_body::BlackBody::new(2000.0).color_through(&air, distance);
+    let near = black_body::BlackBody::new(2000.0).color_for_eye();
+    assert!(color[2] / color[0] < near[2] / near[0]);
+}
+
+#[test]
+fn water_vapor_only_absorbs_in_its_bands() {
+    let dry = Atmosphere::new();
+    let humid = Atmosphere::new().with_water_vapor(0.02);
+    let distance = 5_000.0;
+    let loss =
+        |l: f64| dry.transmittance(l * NANO, distance) - humid.transmittance(l * NANO, distance);
+
+    // band centers lose light
+    for center in [651.0, 724.0, 823.0, 942.0] {
+        assert!(loss(center) > 1.0e-3, "{center} nm lost {}", loss(center));
+    }
+    // the windows between them are left alone, but for the band tails
+    for window in [450.0, 520.0, 680.0, 775.0, 870.0] {
+        assert!(
+            loss(window).abs() < 1.0e-4,
+            "{window} nm lost {}",
+            loss(window)
+        );
+    }
+    // and humidity never adds light
+    for l in wavelengths() {
+        assert!(loss(l) >= 0.0);
+    }
+}
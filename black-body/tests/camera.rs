@@ -0,0 +1,129 @@
+use black_body::camera::{CameraModel, SpectralSensitivity, DAYLIGHT, TUNGSTEN};
+use black_body::BlackBody;
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "expected {expected}, but got {actual}"
+    );
+}
+
+fn two_samples() -> SpectralSensitivity {
+    SpectralSensitivity::new(vec![(500.0, [0.0, 1.0, 0.5]), (600.0, [1.0, 0.0, 0.5])])
+}
+
+#[test]
+fn csv_round_trip() {
+    let preset = SpectralSensitivity::smartphone();
+    let mut text = "wavelength,r,g,b\n# sampled every 10 nm\n".to_string();
+    for l in (380..=1000).step_by(10) {
+        let [r, g, b] = preset.at(l as f64);
+        text += &format!("{l}, {r}, {g}, {b}\n");
+    }
+    let path = std::env::temp_dir().join("black_body_camera_round_trip.csv");
+    std::fs::write(&path, &text).unwrap();
+    let loaded = SpectralSensitivity::load_csv(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for l in (380..=1000).step_by(10) {
+        assert_eq!(loaded.at(l as f64), preset.at(l as f64));
+    }
+}
+
+#[test]
+fn csv_accepts_one_header_only() {
+    let two_headers = "wavelength,r,g,b\nnm,-,-,-\n500,0,1,0\n600,1,0,0\n";
+    assert!(SpectralSensitivity::from_csv(two_headers).is_err());
+    // a corrupt line after the first sample is not a header either
+    let corrupt = "500,0,1,0\n5x0,1,0,0\n600,1,0,0\n";
+    assert!(SpectralSensitivity::from_csv(corrupt).is_err());
+    let columns = "wavelength,r,g,b\n500,0,1\n600,1,0,0\n";
+    assert!(SpectralSensitivity::from_csv(columns).is_err());
+    let short = "wavelength,r,g,b\n500,0,1,0\n";
+    assert!(SpectralSensitivity::from_csv(short).is_err());
+
+    let fine = "\n# comment\nwavelength,r,g,b\n\n600,1,0,0.5\n500,0,1,0.5\n";
+    let sensitivity = SpectralSensitivity::from_csv(fine).unwrap();
+    assert_eq!(sensitivity.at(550.0), two_samples().at(550.0));
+}
+
+#[test]
+fn sensitivity_is_interpolated_and_zero_outside_the_samples() {
+    let sensitivity = two_samples();
+    let [r, g, b] = sensitivity.at(525.0);
+    assert_close(r, 0.25, 1.0e-12);
+    assert_close(g, 0.75, 1.0e-12);
+    assert_close(b, 0.5, 1.0e-12);
+    assert_eq!(sensitivity.at(500.0), [0.0, 1.0, 0.5]);
+    assert_eq!(sensitivity.at(600.0), [1.0, 0.0, 0.5]);
+
+    assert_eq!(sensitivity.at(499.0), [0.0; 3]);
+    assert_eq!(sensitivity.at(601.0), [0.0; 3]);
+}
+
+#[test]
+fn truncated_curves_see_nothing_beyond_their_samples() {
+    // without an IR cut filter, the webcam sees out to 1000 nm
+    let preset = SpectralSensitivity::webcam();
+    let mut text = String::new();
+    for l in (380..=700).step_by(10) {
+        let [r, g, b] = preset.at(l as f64);
+        text += &format!("{l},{r},{g},{b}\n");
+    }
+    let truncated = SpectralSensitivity::from_csv(&text).unwrap();
+    assert_eq!(truncated.at(700.0), preset.at(700.0));
+    assert_eq!(truncated.at(850.0), [0.0; 3]);
+
+    // without the infrared tail, a hot body gives less red signal
+    let spark = BlackBody::new(1500.0);
+    let radiance = |l| spark.radiance(l);
+    let red = |sensitivity| {
+        CameraModel::new(sensitivity)
+            .with_exposure_time(1.0e-9)
+            .raw(&radiance)[0]
+    };
+    assert!(red(truncated) < red(preset));
+}
+
+#[test]
+fn white_balance_at_the_source_temperature_is_neutral() {
+    for kelvin in [TUNGSTEN, DAYLIGHT] {
+        let body = BlackBody::new(kelvin);
+        let camera = CameraModel::new(SpectralSensitivity::dslr())
+            .with_white_balance(kelvin)
+            // dim enough that no channel clips
+            .with_exposure_time(1.0e-9);
+        let raw = camera.raw(&|l| body.radiance(l));
+        assert!(raw.iter().all(|&c| 0.0 < c && c < 1.0), "{raw:?}");
+
+        let [r, g, b] = camera.processed(&|l| body.radiance(l));
+        assert_close(r, g, 1.0e-9);
+        assert_close(b, g, 1.0e-9);
+    }
+}
+
+#[test]
+fn exposure_and_iso_drive_a_channel_into_saturation() {
+    let spark = BlackBody::new(1500.0);
+    let radiance = |l| spark.radiance(l);
+    let camera = CameraModel::new(SpectralSensitivity::smartphone()).with_saturation(0.8);
+    let red = camera.raw(&radiance)[0];
+    assert!(0.0 < red && red < 1.0, "{red}");
+
+    // raw signal is linear below saturation
+    let doubled = camera
+        .clone()
+        .with_exposure_time(2.0 * camera.exposure_time);
+    assert_close(
+        doubled.raw(&radiance)[2],
+        2.0 * camera.raw(&radiance)[2],
+        1.0e-12,
+    );
+
+    let longer = camera
+        .clone()
+        .with_exposure_time(100.0 * camera.exposure_time);
+    assert_eq!(longer.raw(&radiance)[0], 1.0);
+    let brighter = camera.clone().with_iso(100.0 * camera.iso);
+    assert_eq!(brighter.raw(&radiance)[0], 1.0);
+}
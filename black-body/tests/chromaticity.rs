@@ -0,0 +1,60 @@
+use black_body::chromaticity::{Hue, SpectralLocus, D65, EQUAL_ENERGY};
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "expected {expected}, but got {actual}"
+    );
+}
+
+#[test]
+fn monochromatic_points_are_their_own_dominant_wavelength() {
+    let locus = SpectralLocus::new();
+    for wavelength in [470.0, 500.0, 520.0, 550.0, 580.0, 589.0, 610.0] {
+        let xy = SpectralLocus::chromaticity(wavelength);
+        for white in [EQUAL_ENERGY, D65] {
+            let hue = locus.dominant_wavelength(xy, white).unwrap();
+            assert!(matches!(hue, Hue::Dominant(_)), "got {hue:?}");
+            assert_close(hue.wavelength(), wavelength, 0.5);
+            assert_close(locus.excitation_purity(xy, white).unwrap(), 1.0, 1.0e-3);
+        }
+    }
+}
+
+#[test]
+fn purity_scales_with_distance_from_white() {
+    let locus = SpectralLocus::new();
+    let sodium = SpectralLocus::chromaticity(589.0);
+    let white = EQUAL_ENERGY;
+    for ratio in [0.25, 0.5, 0.75] {
+        let xy = [
+            white[0] + (sodium[0] - white[0]) * ratio,
+            white[1] + (sodium[1] - white[1]) * ratio,
+        ];
+        let hue = locus.dominant_wavelength(xy, white).unwrap();
+        assert_close(hue.wavelength(), 589.0, 0.5);
+        assert_close(locus.excitation_purity(xy, white).unwrap(), ratio, 1.0e-3);
+    }
+}
+
+#[test]
+fn purples_have_a_complementary_wavelength() {
+    let locus = SpectralLocus::new();
+    let (violet, red) = locus.range();
+    let a = SpectralLocus::chromaticity(violet);
+    let b = SpectralLocus::chromaticity(red);
+    let purple = [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+    let white = EQUAL_ENERGY;
+
+    let hue = locus.dominant_wavelength(purple, white).unwrap();
+    assert!(matches!(hue, Hue::Complementary(_)), "got {hue:?}");
+    // the complement of a purple is green
+    assert!((490.0..570.0).contains(&hue.wavelength()), "got {hue:?}");
+    assert_close(locus.excitation_purity(purple, white).unwrap(), 1.0, 1.0e-3);
+}
+
+#[test]
+fn white_has_no_purity() {
+    let locus = SpectralLocus::new();
+    assert_eq!(locus.excitation_purity(D65, D65), Some(0.0));
+}
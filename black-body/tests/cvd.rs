@@ -0,0 +1,122 @@
+use black_body::cvd::{Cvd, Deficiency, Method};
+
+const DEFICIENCIES: [Deficiency; 3] = [
+    Deficiency::Protanopia,
+    Deficiency::Deuteranopia,
+    Deficiency::Tritanopia,
+];
+
+// the black body gradient and a few primaries, in linear RGB
+const COLORS: [[f64; 3]; 8] = [
+    [1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, 0.0, 1.0],
+    [1.0, 0.2, 0.0],
+    [1.0, 0.5, 0.1],
+    [1.0, 0.8, 0.4],
+    [0.3, 0.6, 0.9],
+    [0.5, 0.1, 0.7],
+];
+
+fn assert_close(actual: [f64; 3], expected: [f64; 3], tolerance: f64) {
+    let error = (0..3)
+        .map(|i| (actual[i] - expected[i]).abs())
+        .fold(0.0, f64::max);
+    assert!(
+        error < tolerance,
+        "expected {expected:?}, but got {actual:?}"
+    );
+}
+
+// Viénot's plane goes through the sRGB yellow and blue primaries, where
+// Brettel's half planes go through 575 and 475 nm, which fall outside the
+// gamut; so they only agree roughly, and on colors clipped to the screen
+fn display(rgb: [f64; 3]) -> [f64; 3] {
+    rgb.map(|c| c.clamp(0.0, 1.0))
+}
+
+fn dichromats() -> Vec<Cvd> {
+    DEFICIENCIES
+        .into_iter()
+        .flat_map(|deficiency| {
+            [
+                Method::Brettel,
+                Method::Vienot,
+                Method::Machado { severity: 1.0 },
+            ]
+            .map(|method| Cvd::new(deficiency).with_method(method))
+        })
+        .collect()
+}
+
+#[test]
+fn machado_at_no_severity_is_the_identity() {
+    for deficiency in DEFICIENCIES {
+        let cvd = Cvd::new(deficiency).with_method(Method::Machado { severity: 0.0 });
+        assert_eq!(
+            cvd.matrix(),
+            Some([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+        );
+        for color in COLORS {
+            assert_eq!(cvd.simulate(color), color);
+        }
+    }
+}
+
+#[test]
+fn grays_are_seen_alike() {
+    for cvd in dichromats() {
+        for gray in [0.0, 0.25, 1.0] {
+            let color = [gray; 3];
+            assert_close(cvd.simulate(color), color, 1.0e-3);
+        }
+    }
+}
+
+#[test]
+fn projections_keep_their_confusion_line_endpoints() {
+    // the Viénot plane goes through white and the blue primary
+    for deficiency in [Deficiency::Protanopia, Deficiency::Deuteranopia] {
+        let cvd = Cvd::new(deficiency).with_method(Method::Vienot);
+        assert_close(cvd.simulate([0.0, 0.0, 1.0]), [0.0, 0.0, 1.0], 1.0e-6);
+    }
+}
+
+#[test]
+fn projections_are_idempotent() {
+    for cvd in dichromats() {
+        // Machado's matrices are fitted, not projections
+        if matches!(cvd.method, Method::Machado { .. }) {
+            continue;
+        }
+        for color in COLORS {
+            let once = cvd.simulate(color);
+            assert_close(cvd.simulate(once), once, 1.0e-6);
+        }
+    }
+}
+
+#[test]
+fn vienot_agrees_with_brettel_on_red_green() {
+    for deficiency in [Deficiency::Protanopia, Deficiency::Deuteranopia] {
+        let brettel = Cvd::new(deficiency);
+        let vienot = Cvd::new(deficiency).with_method(Method::Vienot);
+        for color in COLORS {
+            let [a, b] = [vienot, brettel].map(|cvd| display(cvd.simulate(color)));
+            assert_close(a, b, 0.2);
+        }
+    }
+}
+
+#[test]
+fn deficiencies_parse_by_name() {
+    for deficiency in DEFICIENCIES {
+        let name = deficiency.to_string();
+        assert_eq!(name.parse::<Deficiency>(), Ok(deficiency));
+        assert_eq!(name.to_uppercase().parse::<Deficiency>(), Ok(deficiency));
+    }
+    assert_eq!("deutan".parse::<Deficiency>(), Ok(Deficiency::Deuteranopia));
+    for unknown in ["", "achromatopsia", "protanomaly", "tritanopia2"] {
+        assert!(unknown.parse::<Deficiency>().is_err(), "{unknown}");
+    }
+}
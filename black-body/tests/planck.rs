@@ -0,0 +1,94 @@
+use black_body::{conversion, BlackBody};
+
+const STEFAN_BOLTZMANN: f64 = 5.670374419e-8; // [W m^-2 K^-4]
+const APERY: f64 = 1.2020569031595942; // ζ(3)
+const C: f64 = 2.99792458e8;
+const H: f64 = 6.62607015e-34;
+const K: f64 = 1.380649e-23;
+
+// trapezoidal rule on a logarithmic grid, which suits the long Planck tails
+fn integrate(f: impl Fn(f64) -> f64, lower: f64, upper: f64) -> f64 {
+    let steps = 20_000;
+    let (a, b) = (lower.ln(), upper.ln());
+    let dx = (b - a) / steps as f64;
+    (0..=steps)
+        .map(|i| {
+            let x = (a + i as f64 * dx).exp();
+            let weight = if i == 0 || i == steps { 0.5 } else { 1.0 };
+            weight * f(x) * x * dx
+        })
+        .sum()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    let error = ((actual - expected) / expected).abs();
+    assert!(error < 1.0e-4, "expected {expected}, but got {actual}");
+}
+
+#[test]
+fn integrals_of_every_form_agree_with_stefan_boltzmann() {
+    for temperature in [300.0, 1273.0, 5800.0] {
+        let body = BlackBody::new(temperature);
+        let expected = STEFAN_BOLTZMANN * temperature.powi(4) / std::f64::consts::PI;
+
+        let per_wavelength = integrate(|l| body.radiance(l), 1.0e-8, 1.0e-2);
+        let per_frequency = integrate(|v| body.radiance_per_frequency(v), 1.0e9, 1.0e17);
+        let per_wavenumber = integrate(|n| body.radiance_per_wavenumber(n), 1.0e0, 1.0e8);
+
+        assert_close(per_wavelength, expected);
+        assert_close(per_frequency, expected);
+        assert_close(per_wavenumber, expected);
+    }
+}
+
+#[test]
+fn photon_integrals_agree() {
+    for temperature in [300.0, 1273.0, 5800.0] {
+        let body = BlackBody::new(temperature);
+        // ∫ 2ν² / c² / (exp(hν/kT) - 1) dν = 4 ζ(3) (kT/h)³ / c²
+        let expected = 4.0 * APERY * (K * temperature / H).powi(3) / C.powi(2);
+
+        let per_wavelength = integrate(|l| body.photon_radiance(l), 1.0e-8, 1.0e-2);
+        let per_frequency = integrate(|v| body.photon_radiance_per_frequency(v), 1.0e9, 1.0e17);
+
+        assert_close(per_wavelength, expected);
+        assert_close(per_frequency, expected);
+    }
+}
+
+#[test]
+fn densities_convert_between_forms() {
+    let body = BlackBody::new(1273.0);
+    for wavelength in [400.0e-9, 550.0e-9, 2.0e-6] {
+        let frequency = conversion::wavelength_to_frequency(wavelength);
+        let wavenumber = conversion::wavelength_to_wavenumber(wavelength);
+        let per_wavelength = body.radiance(wavelength);
+
+        assert_close(
+            conversion::per_wavelength_to_per_frequency(per_wavelength, wavelength),
+            body.radiance_per_frequency(frequency),
+        );
+        assert_close(
+            conversion::per_wavelength_to_per_wavenumber(per_wavelength, wavelength),
+            body.radiance_per_wavenumber(wavenumber),
+        );
+        assert_close(
+            conversion::per_frequency_to_per_wavelength(
+                body.radiance_per_frequency(frequency),
+                frequency,
+            ),
+            per_wavelength,
+        );
+        assert_close(
+            conversion::per_wavenumber_to_per_wavelength(
+                body.radiance_per_wavenumber(wavenumber),
+                wavenumber,
+            ),
+            per_wavelength,
+        );
+        assert_close(
+            conversion::photons_to_energy(body.photon_radiance(wavelength), wavelength),
+            per_wavelength,
+        );
+    }
+}
@@ -1,196 +1,97 @@
-extern crate nalgebra_glm as glm;
-
-use std::f32::consts;
-use wasm_bindgen::{prelude::*, JsCast};
-use web_sys::{WebGl2RenderingContext as GL, *};
-
-use super::fire::Fire;
-use super::shader::*;
-
-pub struct App {
-    pub gl: GL,
-    width: u32,
-    height: u32,
-    pub shader_program: WebGlProgram,
+use super::camera::controls::{Controls, Input};
+use super::camera::Camera;
+use super::fire::kernel::KernelSimulation;
+use super::fire::{Fire, TIME_DELTA};
+use super::frame::FrameData;
+use super::haze::Haze;
+use super::renderer::Renderer;
+use super::timestep::FixedTimestep;
+
+// longest camera step [s], so that the camera does not jump after a pause,
+// as the clock caps its substeps
+const MAX_CAMERA_STEP: f32 = 0.1;
+
+/// Steps the simulation in real time and hands each frame to a renderer.
+pub struct App<R: Renderer> {
+    pub renderer: R,
     pub model: Fire,
+    pub clock: FixedTimestep,
+    /// draws the sparks as instances shaded on the GPU, with their trails
+    /// shortened to the last step
+    pub instanced: bool,
+    /// runs these sparks in place of the model, on the GPU where the
+    /// renderer has one
+    pub simulation: Option<KernelSimulation>,
+    pub camera: Camera,
+    /// tints the frame by the distance of every vertex from the eye; the
+    /// sparks shaded on the GPU are left as they are
+    pub haze: Option<Haze>,
+    controls: Controls,
+    // of the last frame [ms]; the camera moves in real time, however slow
+    // the simulation runs
+    last_timestamp: Option<f64>,
 }
 
-impl App {
-    pub fn new(height: u32, width: u32, model: Fire) -> Result<Self, JsValue> {
-        let window = Self::init_window()?;
-        let document = Self::init_document(&window)?;
-        let canvas = Self::init_canvas(width, height, &document)?;
-        let gl = Self::init_gl(&canvas)?;
-        let shader_program = Self::init_shader_program(&gl)?;
-
-        Ok(Self {
-            gl,
-            width,
-            height,
-            shader_program,
+impl<R: Renderer> App<R> {
+    pub fn new(renderer: R, model: Fire) -> Self {
+        Self {
+            renderer,
             model,
-        })
-    }
-
-    fn init_window() -> Result<Window, JsValue> {
-        web_sys::window().ok_or_else(|| JsValue::from_str("Failed to get window"))
-    }
-
-    fn init_document(window: &Window) -> Result<Document, JsValue> {
-        window
-            .document()
-            .ok_or_else(|| JsValue::from_str("Failed to get document"))
-    }
-
-    fn init_canvas(
-        width: u32,
-        height: u32,
-        document: &Document,
-    ) -> Result<HtmlCanvasElement, JsValue> {
-        let canvas = document
-            .get_element_by_id("canvas")
-            .map(|e| e.dyn_into::<HtmlCanvasElement>())
-            .ok_or_else(|| JsValue::from_str("Failed to get canvas"))??;
-
-        canvas.set_width(width);
-        canvas.set_height(height);
-        Ok(canvas)
-    }
-
-    fn init_gl(canvas: &HtmlCanvasElement) -> Result<GL, JsValue> {
-        canvas
-            .get_context("webgl2")
-            .and_then(|op| {
-                op.ok_or_else(|| JsValue::from_str("Failed to get WebGl2RenderingContext"))
-            })
-            .and_then(|context| {
-                context.dyn_into::<GL>().map_err(|_| {
-                    JsValue::from_str("Failed to cast context to WebGl2RenderingContext")
-                })
-            })
-    }
-
-    fn init_shader_program(gl: &GL) -> Result<WebGlProgram, JsValue> {
-        let vertex_shader =
-            create_shader(&gl, GL::VERTEX_SHADER, include_str!("shader/vertex.glsl"))?;
-        let fragment_shader = create_shader(
-            &gl,
-            GL::FRAGMENT_SHADER,
-            include_str!("shader/fragment.glsl"),
-        )?;
-        let program = link_program(gl, &vertex_shader, &fragment_shader)?;
-        Ok(program)
-    }
-
-    pub fn render(&mut self) -> Result<(), JsValue> {
-        self.gl.use_program(Some(&self.shader_program));
-        let (vertices, colors, links) = self.model.update();
-
-        let vbo_data: &[&[f32]] = &[&vertices, &colors];
-        let locations = &[0, 1];
-        let vertex_count = vertices.len() as i32 / 3;
-
-        let vao = self.create_vao(vbo_data, locations, &links, vertex_count)?;
-        self.gl.bind_vertex_array(Some(&vao));
-
-        let mvp_location = self
-            .gl
-            .get_uniform_location(&self.shader_program, "mvpMatrix")
-            .ok_or("Failed to get uniform location")?;
-
-        self.gl.enable(GL::DEPTH_TEST);
-        self.gl.depth_func(GL::LEQUAL);
-        self.gl.enable(GL::CULL_FACE);
-
-        // 視点を定義
-        self.send_mvp_matrix(&mvp_location);
-
-        // 描画
-        let link_count = links.len() as i32;
-        self.draw(link_count);
-
-        Ok(())
+            clock: FixedTimestep::new(TIME_DELTA),
+            instanced: false,
+            simulation: None,
+            camera: Camera::default(),
+            haze: None,
+            controls: Controls::default(),
+            last_timestamp: None,
+        }
     }
 
-    fn send_mvp_matrix(&self, location: &WebGlUniformLocation) {
-        let eye = glm::Vec3::new(0.0, 0.0, 3.0);
-        let center = glm::Vec3::new(0.0, 0.0, 0.0);
-        let up = glm::Vec3::new(0.0, 1.0, 0.0);
-        let view_matrix = glm::look_at(&eye, &center, &up);
-
-        let aspect = self.width as f32 / self.height as f32;
-        let fovy = 90.0 * consts::PI / 180.0;
-        let near = 0.1;
-        let far = 100.0;
-        let projection_matrix = glm::perspective(aspect, fovy, near, far);
-
-        let mvp_matrix = projection_matrix * view_matrix;
-        let mvp_arrays: [[f32; 4]; 4] = mvp_matrix.into();
-        let mvp_matrices = mvp_arrays.iter().flat_map(|a| *a).collect::<Vec<_>>();
-
-        self.gl
-            .uniform_matrix4fv_with_f32_array_and_src_offset_and_src_length(
-                Some(location),
-                false,
-                &mvp_matrices,
-                0,
-                0,
-            );
+    /// Moves the camera by a pointer or wheel event.
+    pub fn input(&mut self, input: Input) {
+        self.controls.handle(&mut self.camera, input);
     }
 
-    fn create_vao(
-        &self,
-        vbo_data: &[&[f32]],
-        locations: &[u32],
-        ibo_data: &[u16],
-        vertex_count: i32,
-    ) -> Result<WebGlVertexArrayObject, String> {
-        let vao = self
-            .gl
-            .create_vertex_array()
-            .ok_or("Failed to create vertex array object")?;
-        self.gl.bind_vertex_array(Some(&vao));
-
-        for i in 0..vbo_data.len() {
-            let vbo = self.gl.create_buffer().ok_or("Failed to create buffer")?;
-            self.gl.bind_buffer(GL::ARRAY_BUFFER, Some(&vbo));
-            unsafe {
-                let view = js_sys::Float32Array::view(&vbo_data[i]);
-                self.gl.buffer_data_with_array_buffer_view(
-                    GL::ARRAY_BUFFER,
-                    &view,
-                    GL::STATIC_DRAW,
-                );
+    /// Renders the frame for a `requestAnimationFrame` timestamp [ms].
+    pub fn render(&mut self, timestamp: f64) -> Result<(), R::Error> {
+        let elapsed = self
+            .last_timestamp
+            .map_or(0.0, |last| ((timestamp - last) / 1000.0).max(0.0) as f32)
+            .min(MAX_CAMERA_STEP);
+        self.last_timestamp = Some(timestamp);
+        self.camera.update(elapsed);
+        self.renderer.set_camera(&self.camera);
+
+        for _ in 0..self.clock.advance(timestamp) {
+            match self.simulation.as_mut() {
+                Some(simulation) => self.renderer.simulate(simulation)?,
+                None => self.model.step(),
             }
-            self.gl.enable_vertex_attrib_array(locations[i]);
-            let size = vbo_data[i].len() as i32 / vertex_count;
-            self.gl
-                .vertex_attrib_pointer_with_i32(locations[i], size, GL::FLOAT, false, 0, 0);
         }
-
-        let ibo = self.gl.create_buffer().ok_or("Failed to create buffer")?;
-        self.gl.bind_buffer(GL::ELEMENT_ARRAY_BUFFER, Some(&ibo));
-        unsafe {
-            let view = js_sys::Uint16Array::view(ibo_data);
-            self.gl.buffer_data_with_array_buffer_view(
-                GL::ELEMENT_ARRAY_BUFFER,
-                &view,
-                GL::STATIC_DRAW,
-            );
+        if let Some(simulation) = self.simulation.as_ref() {
+            return self
+                .renderer
+                .render_simulation(simulation, &FrameData::default());
         }
-
-        self.gl.bind_vertex_array(None);
-
-        Ok(vao)
+        let alpha = self.clock.alpha();
+        if !self.instanced {
+            let frame = self.attenuated(self.model.frame(alpha));
+            return self.renderer.render(&frame);
+        }
+        let instances = self.model.instances(alpha);
+        let hinotama = self
+            .model
+            .hinotama()
+            .map(|h| h.frame(alpha))
+            .unwrap_or_default();
+        let hinotama = self.attenuated(hinotama);
+        self.renderer.render_instanced(&instances, &hinotama)
     }
 
-    fn draw(&self, index_count: i32) {
-        self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        self.gl.clear_depth(1.0);
-        self.gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
-
-        self.gl
-            .draw_elements_with_i32(GL::LINES, index_count, GL::UNSIGNED_SHORT, 0);
-        self.gl.flush();
+    fn attenuated(&self, mut frame: FrameData) -> FrameData {
+        if let Some(haze) = self.haze.as_ref() {
+            frame.attenuate(haze, self.camera.eye());
+        }
+        frame
     }
 }
@@ -0,0 +1,26 @@
+use fire::fire::Fire;
+use fire::renderer::hdr::Hdr;
+use fire::renderer::software::SoftwareRenderer;
+
+// usage: render_frames [output directory] [frames] [width] [height]
+//                      [reinhard|aces|agx|off]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let directory = args.get(1).map_or("artifacts/frames", |a| a.as_str());
+    let frames = args.get(2).map_or(Ok(60), |a| a.parse())?;
+    let width = args.get(3).map_or(Ok(1280), |a| a.parse())?;
+    let height = args.get(4).map_or(Ok(800), |a| a.parse())?;
+    let hdr = match args.get(5).map(|a| a.as_str()) {
+        Some("off") => None,
+        Some(name) => Some(Hdr::default().with_tone_mapping(name.parse()?)),
+        None => Some(Hdr::default()),
+    };
+
+    let mut fire = Fire::new();
+    let mut renderer = SoftwareRenderer::new(width, height);
+    renderer.set_hdr(hdr);
+    renderer.render_sequence(&mut fire, frames, directory)?;
+
+    println!("wrote {frames} frames to {directory}");
+    Ok(())
+}
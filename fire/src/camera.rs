@@ -0,0 +1,235 @@
+pub mod controls;
+
+extern crate nalgebra_glm as glm;
+
+use std::f32::consts;
+
+/// Vertical field of view.
+pub const FOVY: f32 = 90.0 * consts::PI / 180.0;
+const NEAR: f32 = 0.1; // [m]
+const FAR: f32 = 100.0; // [m]
+
+// flings slower than these stop
+const MIN_ORBIT_SPEED: f32 = 1.0e-3; // [rad/s]
+const MIN_PAN_SPEED: f32 = 1.0e-4; // [m/s]
+
+/// How far the camera may be moved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// range of distances from the target [m]
+    pub distance: (f32, f32),
+    /// range of elevations above the target's horizon [rad]
+    pub pitch: (f32, f32),
+    /// farthest the target may be panned from the origin [m]
+    pub pan_radius: f32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            distance: (0.5, 20.0),
+            // short of straight up and down, where the view would flip
+            pitch: (-85.0f32.to_radians(), 85.0f32.to_radians()),
+            pan_radius: 5.0,
+        }
+    }
+}
+
+impl Limits {
+    pub fn with_distance(mut self, min: f32, max: f32) -> Self {
+        assert!(
+            0.0 < min && min <= max,
+            "it requires; 0 < min <= max\n\
+            min must be grater than 0 and at most max,\n\
+            but got {min} and {max}"
+        );
+        self.distance = (min, max);
+        self
+    }
+
+    pub fn with_pitch(mut self, min: f32, max: f32) -> Self {
+        let right = 0.5 * consts::PI;
+        assert!(
+            -right < min && min <= max && max < right,
+            "it requires; -pi/2 < min <= max < pi/2\n\
+            min and max must be within a right angle of the horizon,\n\
+            but got {min} and {max}"
+        );
+        self.pitch = (min, max);
+        self
+    }
+
+    pub fn with_pan_radius(mut self, pan_radius: f32) -> Self {
+        assert!(
+            pan_radius >= 0.0,
+            "it requires; pan_radius >= 0\n\
+            pan_radius must be grater than or equal to 0,\n\
+            but got {pan_radius}"
+        );
+        self.pan_radius = pan_radius;
+        self
+    }
+}
+
+/// Camera orbiting a target point, which carries on turning and sliding for
+/// a while after a fling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    /// point looked at [m]
+    pub target: [f32; 3],
+    /// from the target [m]
+    pub distance: f32,
+    /// turn around the vertical axis, from looking down -z [rad]
+    pub yaw: f32,
+    /// elevation of the eye above the target's horizon [rad]
+    pub pitch: f32,
+    pub limits: Limits,
+    /// rate at which flings slow down [1/s]
+    pub damping: f32,
+    // yaw and pitch [rad/s]
+    orbit_velocity: [f32; 2],
+    // right and up across the view [m/s]
+    pan_velocity: [f32; 2],
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            target: [0.0; 3],
+            distance: 3.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            limits: Limits::default(),
+            damping: 4.0,
+            orbit_velocity: [0.0; 2],
+            pan_velocity: [0.0; 2],
+        }
+    }
+}
+
+impl Camera {
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self.orbit(0.0, 0.0);
+        self.pan(0.0, 0.0);
+        self.zoom(1.0);
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        assert!(
+            damping > 0.0,
+            "it requires; damping > 0\n\
+            damping must be grater than 0,\n\
+            but got {damping}"
+        );
+        self.damping = damping;
+        self
+    }
+
+    /// Position of the eye [m].
+    pub fn eye(&self) -> [f32; 3] {
+        let (sin_yaw, cos_yaw) = (libm::sinf(self.yaw), libm::cosf(self.yaw));
+        let (sin_pitch, cos_pitch) = (libm::sinf(self.pitch), libm::cosf(self.pitch));
+        let offset = [cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw];
+        [0, 1, 2].map(|i| self.target[i] + self.distance * offset[i])
+    }
+
+    /// Unit vector to the right of the view.
+    pub fn right(&self) -> [f32; 3] {
+        [libm::cosf(self.yaw), 0.0, -libm::sinf(self.yaw)]
+    }
+
+    /// Unit vector up the view.
+    pub fn up(&self) -> [f32; 3] {
+        let (sin_yaw, cos_yaw) = (libm::sinf(self.yaw), libm::cosf(self.yaw));
+        let (sin_pitch, cos_pitch) = (libm::sinf(self.pitch), libm::cosf(self.pitch));
+        [-sin_pitch * sin_yaw, cos_pitch, -sin_pitch * cos_yaw]
+    }
+
+    /// Meters spanned by the height of the view, at the target.
+    pub fn view_height(&self) -> f32 {
+        2.0 * self.distance * libm::tanf(0.5 * FOVY)
+    }
+
+    /// Turns around the target, within the pitch limits.
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        let (min, max) = self.limits.pitch;
+        self.yaw = (self.yaw + yaw) % (2.0 * consts::PI);
+        self.pitch = (self.pitch + pitch).clamp(min, max);
+    }
+
+    /// Slides the target across the view [m], within the pan radius.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        let (r, u) = (self.right(), self.up());
+        let mut target = [0, 1, 2].map(|i| self.target[i] + right * r[i] + up * u[i]);
+        let norm = target.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > self.limits.pan_radius {
+            target = target.map(|x| x * self.limits.pan_radius / norm);
+        }
+        self.target = target;
+    }
+
+    /// Moves `factor` times as far from the target, within the distance
+    /// limits; below 1 closes in.
+    pub fn zoom(&mut self, factor: f32) {
+        let (min, max) = self.limits.distance;
+        self.distance = (self.distance * factor).clamp(min, max);
+    }
+
+    /// Keeps turning at `velocity` (yaw and pitch [rad/s]) until damped.
+    pub fn fling_orbit(&mut self, velocity: [f32; 2]) {
+        self.orbit_velocity = velocity;
+    }
+
+    /// Keeps sliding at `velocity` (right and up [m/s]) until damped.
+    pub fn fling_pan(&mut self, velocity: [f32; 2]) {
+        self.pan_velocity = velocity;
+    }
+
+    /// Stops a fling, as when grabbed.
+    pub fn stop(&mut self) {
+        self.orbit_velocity = [0.0; 2];
+        self.pan_velocity = [0.0; 2];
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.orbit_velocity != [0.0; 2] || self.pan_velocity != [0.0; 2]
+    }
+
+    /// Carries a fling on over `dt` [s] of real time.
+    pub fn update(&mut self, dt: f32) {
+        let [yaw, pitch] = self.orbit_velocity;
+        self.orbit(yaw * dt, pitch * dt);
+        let [right, up] = self.pan_velocity;
+        self.pan(right * dt, up * dt);
+
+        let decay = libm::expf(-self.damping * dt);
+        let damp = |velocity: [f32; 2], min: f32| {
+            let velocity = velocity.map(|v| v * decay);
+            let speed = velocity.iter().map(|v| v * v).sum::<f32>().sqrt();
+            if speed < min {
+                [0.0; 2]
+            } else {
+                velocity
+            }
+        };
+        self.orbit_velocity = damp(self.orbit_velocity, MIN_ORBIT_SPEED);
+        self.pan_velocity = damp(self.pan_velocity, MIN_PAN_SPEED);
+    }
+
+    pub fn view_matrix(&self) -> glm::Mat4 {
+        let eye = glm::Vec3::from(self.eye());
+        let center = glm::Vec3::from(self.target);
+        let up = glm::Vec3::new(0.0, 1.0, 0.0);
+        glm::look_at(&eye, &center, &up)
+    }
+
+    /// Model-view-projection matrix of a `width` by `height` viewport,
+    /// shared by every backend.
+    pub fn mvp_matrix(&self, width: u32, height: u32) -> glm::Mat4 {
+        let aspect = width as f32 / height as f32;
+        let projection_matrix = glm::perspective(aspect, FOVY, NEAR, FAR);
+        projection_matrix * self.view_matrix()
+    }
+}
@@ -0,0 +1,202 @@
+//! Pointer and wheel input turned into camera moves, independent of the
+//! browser: the page translates its events into `Input` and feeds them in.
+use std::f32::consts;
+
+use super::Camera;
+
+// a drag across the height of the view turns half a turn
+const ORBIT_PER_HEIGHT: f32 = consts::PI; // [rad]
+
+// distance factor of one wheel notch
+const ZOOM_PER_NOTCH: f32 = 1.1;
+// a pointer held still this long before release does not fling
+const FLING_WINDOW: f64 = 50.0; // [ms]
+
+// weight of the latest move in the release velocity
+const VELOCITY_SMOOTHING: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    /// left mouse button, pen or touch; orbits
+    Primary,
+    /// right or middle mouse button; pans
+    Secondary,
+}
+
+/// A pointer or wheel event; positions are in heights of the view from its
+/// top left corner, and times in [ms].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    Down {
+        id: i32,
+        position: [f32; 2],
+        button: Button,
+        time: f64,
+    },
+    Move {
+        id: i32,
+        position: [f32; 2],
+        time: f64,
+    },
+    /// released, or cancelled by the browser
+    Up { id: i32, time: f64 },
+    /// turned by `notches`, positive toward the user, which backs away
+    Wheel { notches: f32 },
+}
+
+/// What the pointers down are doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Idle,
+    Orbit,
+    Pan,
+    /// two pointers; spreading them zooms in, and moving them together pans
+    Pinch,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pointer {
+    id: i32,
+    position: [f32; 2],
+    button: Button,
+}
+
+/// State machine from `Input` to camera moves.
+#[derive(Debug, Clone, Default)]
+pub struct Controls {
+    // in the order they went down
+    pointers: Vec<Pointer>,
+    // of the drag [heights/s], for the fling on release
+    velocity: [f32; 2],
+    last_move: f64,
+}
+
+impl Controls {
+    pub fn gesture(&self) -> Gesture {
+        match self.pointers.as_slice() {
+            [] => Gesture::Idle,
+            [pointer] => match pointer.button {
+                Button::Primary => Gesture::Orbit,
+                Button::Secondary => Gesture::Pan,
+            },
+            _ => Gesture::Pinch,
+        }
+    }
+
+    pub fn handle(&mut self, camera: &mut Camera, input: Input) {
+        match input {
+            Input::Down {
+                id,
+                position,
+                button,
+                time,
+            } => {
+                // grabbing stops a fling
+                camera.stop();
+                self.pointers.retain(|p| p.id != id);
+                self.pointers.push(Pointer {
+                    id,
+                    position,
+                    button,
+                });
+                self.velocity = [0.0; 2];
+                self.last_move = time;
+            }
+            Input::Move { id, position, time } => self.drag(camera, id, position, time),
+            Input::Up { id, time } => {
+                let gesture = self.gesture();
+                let Some(index) = self.pointers.iter().position(|p| p.id == id) else {
+                    return;
+                };
+                self.pointers.remove(index);
+                // a drag let go while moving carries on; the end of a pinch
+                // does not
+                if self.pointers.is_empty() && time - self.last_move < FLING_WINDOW {
+                    self.fling(camera, gesture);
+                }
+                self.velocity = [0.0; 2];
+            }
+            Input::Wheel { notches } => camera.zoom(libm::powf(ZOOM_PER_NOTCH, notches)),
+        }
+    }
+
+    fn drag(&mut self, camera: &mut Camera, id: i32, position: [f32; 2], time: f64) {
+        let gesture = self.gesture();
+        let Some(index) = self.pointers.iter().position(|p| p.id == id) else {
+            // hovering
+            return;
+        };
+        if gesture == Gesture::Pinch {
+            // only the first two pointers pinch
+            if index < 2 {
+                self.pinch(camera, index, position);
+            }
+            self.pointers[index].position = position;
+            return;
+        }
+
+        let last = self.pointers[index].position;
+        let delta = [position[0] - last[0], position[1] - last[1]];
+        self.pointers[index].position = position;
+        Self::apply(camera, gesture, delta);
+
+        let elapsed = (time - self.last_move) as f32 / 1000.0;
+        if elapsed > 0.0 {
+            for (v, d) in self.velocity.iter_mut().zip(delta) {
+                *v += VELOCITY_SMOOTHING * (d / elapsed - *v);
+            }
+            self.last_move = time;
+        }
+    }
+
+    fn pinch(&mut self, camera: &mut Camera, index: usize, position: [f32; 2]) {
+        let (a, b) = (self.pointers[0].position, self.pointers[1].position);
+        let (new_a, new_b) = if index == 0 {
+            (position, b)
+        } else {
+            (a, position)
+        };
+        let spread = |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).hypot(a[1] - b[1]);
+        let (before, after) = (spread(a, b), spread(new_a, new_b));
+        if before > 0.0 && after > 0.0 {
+            camera.zoom(before / after);
+        }
+        let delta = [0, 1].map(|i| 0.5 * (new_a[i] + new_b[i] - a[i] - b[i]));
+        Self::apply(camera, Gesture::Pan, delta);
+    }
+
+    fn fling(&self, camera: &mut Camera, gesture: Gesture) {
+        match gesture {
+            Gesture::Orbit => camera.fling_orbit(Self::orbit(self.velocity)),
+            Gesture::Pan => camera.fling_pan(Self::pan(camera, self.velocity)),
+            _ => {}
+        }
+    }
+
+    // moves the camera so that the scene follows a drag of `delta` heights
+    fn apply(camera: &mut Camera, gesture: Gesture, delta: [f32; 2]) {
+        match gesture {
+            Gesture::Orbit => {
+                let [yaw, pitch] = Self::orbit(delta);
+                camera.orbit(yaw, pitch);
+            }
+            Gesture::Pan => {
+                let [right, up] = Self::pan(camera, delta);
+                camera.pan(right, up);
+            }
+            _ => {}
+        }
+    }
+
+    // dragging right turns the scene right, so the eye goes left; dragging
+    // down tips the scene toward the viewer, so the eye goes up
+    fn orbit(drag: [f32; 2]) -> [f32; 2] {
+        [-drag[0] * ORBIT_PER_HEIGHT, drag[1] * ORBIT_PER_HEIGHT]
+    }
+
+    // the point under the pointer stays under it, at the target's depth
+    fn pan(camera: &Camera, drag: [f32; 2]) -> [f32; 2] {
+        let meters = camera.view_height();
+        [-drag[0] * meters, drag[1] * meters]
+    }
+}
@@ -1,130 +1,316 @@
-use black_body::BlackBody;
-use rand::prelude::*;
-use std::f32::consts;
-use std::iter::zip;
+pub mod air;
+pub mod branching;
+pub mod emitter;
+pub mod hinotama;
+pub mod integrator;
+pub mod kernel;
+pub mod particle;
+pub mod physics;
+pub mod thermal;
+pub mod timeline;
+pub mod trail;
+
+use super::frame::{FrameData, Indices, Instances};
+use super::rng::Pcg32;
+use branching::Branching;
+use emitter::Emitter;
+use hinotama::Hinotama;
+use particle::Particle;
+use physics::Physics;
+use timeline::{Event, Phase, Timeline};
+use trail::TrailStyle;
 
 const GRAVITY: f32 = 9.81;
 const KELVIN: f32 = 273.0;
 
-const TIME_DELTA: f32 = 0.010;
-const AIR_RESISTANCE: f32 = 0.1;
+/// simulated time per step [s]
+pub const TIME_DELTA: f32 = 0.010;
 const MAX_SPEED: f32 = 50.0;
 
-#[derive(Clone)]
-struct Particle {
-    position: [f32; 3],
-    color: [f32; 4],
-    velocity: [f32; 3],
-    temperature: f32,
+// Draper point; below this a spark no longer glows visibly
+const VISIBLE_TEMPERATURE: f32 = 798.0; // [K]
+
+// vertices of every trail must fit in u32 indices
+const MAX_VERTICES: usize = u32::MAX as usize;
+
+#[derive(Debug, Clone)]
+pub struct FireConfig {
+    /// seed of the random number generator; equal seeds replay identically
+    pub seed: u64,
+    pub emitter: Emitter,
+    /// size of the particle pool; emission pauses while it is full
+    pub max_particles: usize,
+    /// half size of the box around the origin sparks may fly in [m]
+    pub bounds: f32,
+    pub trail: TrailStyle,
+    pub branching: Branching,
+    pub physics: Physics,
+    /// molten ball carrying the emitter; `None` emits from a fixed point
+    pub hinotama: Option<Hinotama>,
+    /// burn phases overriding the emitter and the branching rate; `None`
+    /// burns steadily forever
+    pub timeline: Option<Timeline>,
 }
 
-impl Particle {
-    pub fn new(rng: &mut ThreadRng) -> Self {
+impl Default for FireConfig {
+    fn default() -> Self {
         Self {
-            position: [0.0, 0.5, 0.0],
-            color: [1.0, 1.0, 1.0, 1.0],
-            velocity: Self::init_velocity(
-                rng.gen_range(0.0, MAX_SPEED),
-                rng.gen_range(0.0, 2.0 * consts::PI),
-                rng.gen_range(0.0, 2.0 * consts::PI),
-            ),
-            temperature: rng.gen_range(800.0 + KELVIN, 1000.0 + KELVIN),
+            seed: 0x5E4C0,
+            emitter: Emitter::default(),
+            max_particles: 2000,
+            bounds: 20.0,
+            trail: TrailStyle::default(),
+            branching: Branching::default(),
+            physics: Physics::default(),
+            hinotama: None,
+            timeline: None,
         }
     }
+}
+
+pub struct Fire {
+    particles: Vec<Particle>,
+    max_particles: usize,
+    emitter: Emitter,
+    rng: Pcg32,
+    bounds: f32,
+    trail: TrailStyle,
+    branching: Branching,
+    physics: Physics,
+    hinotama: Option<Hinotama>,
+    timeline: Option<Timeline>,
+    events: Vec<Event>,
+}
 
-    fn init_velocity(velocity: f32, latitude: f32, longitude: f32) -> [f32; 3] {
-        [
-            latitude.cos() * longitude.sin() * velocity,
-            latitude.cos() * longitude.cos() * velocity,
-            latitude.sin() * velocity,
-        ]
+impl Default for Fire {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn update(&mut self) {
-        self.update_position();
-        self.update_velocity();
-        self.update_color();
+impl Fire {
+    pub fn new() -> Self {
+        Self::with_config(FireConfig::default())
     }
 
-    fn update_position(&mut self) {
-        self.position[0] += self.velocity[0] * TIME_DELTA;
-        self.position[1] += self.velocity[1] * TIME_DELTA;
-        self.position[2] += self.velocity[2] * TIME_DELTA;
+    pub fn with_config(config: FireConfig) -> Self {
+        assert!(
+            config.trail.length > 0,
+            "it requires; trail.length > 0\n\
+            trail.length must be grater than 0,\n\
+            but got {}",
+            config.trail.length
+        );
+        let max_particles = MAX_VERTICES / (config.trail.length + 1);
+        assert!(
+            config.max_particles <= max_particles,
+            "it requires; max_particles <= {max_particles}\n\
+            max_particles must be less than or equal to {max_particles},\n\
+            but got {}",
+            config.max_particles
+        );
+
+        Self {
+            // allocated once; dead sparks free their slot for new ones
+            particles: Vec::with_capacity(config.max_particles),
+            max_particles: config.max_particles,
+            emitter: config.emitter,
+            rng: Pcg32::new(config.seed),
+            bounds: config.bounds,
+            trail: config.trail,
+            branching: config.branching,
+            physics: config.physics,
+            hinotama: config.hinotama,
+            timeline: config.timeline,
+            events: vec![],
+        }
     }
 
-    fn update_velocity(&mut self) {
-        let air = |v: f32| -> f32 { -v * (AIR_RESISTANCE * TIME_DELTA) };
-        self.velocity[0] += air(self.velocity[0]);
-        self.velocity[1] += air(self.velocity[1]) - (GRAVITY * TIME_DELTA);
-        self.velocity[2] += air(self.velocity[2]);
+    pub fn emitter_mut(&mut self) -> &mut Emitter {
+        &mut self.emitter
     }
 
-    fn update_color(&mut self) {
-        let speed = [
-            self.velocity[0].powi(2),
-            self.velocity[1].powi(2),
-            self.velocity[2].powi(2),
-        ]
-        .iter()
-        .sum::<f32>()
-        .sqrt();
+    pub fn branching_mut(&mut self) -> &mut Branching {
+        &mut self.branching
+    }
 
-        let intensity = 1.0 * speed / MAX_SPEED;
+    pub fn hinotama(&self) -> Option<&Hinotama> {
+        self.hinotama.as_ref()
+    }
 
-        let black_body = BlackBody::new(self.temperature as f64);
-        let color = black_body.color_for_eye();
+    pub fn hinotama_mut(&mut self) -> Option<&mut Hinotama> {
+        self.hinotama.as_mut()
+    }
 
-        self.color[0] = intensity * color[0] as f32;
-        self.color[1] = intensity * color[1] as f32;
-        self.color[2] = intensity * color[2] as f32;
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
     }
-}
 
-pub struct Fire {
-    particles: Vec<Particle>,
-}
+    pub fn timeline(&self) -> Option<&Timeline> {
+        self.timeline.as_ref()
+    }
 
-impl Fire {
-    pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
-        let particles = (0..100)
-            .map(|_| Particle::new(&mut rng))
-            .collect::<Vec<Particle>>();
+    /// Current burn phase, if a timeline is running.
+    pub fn phase(&self) -> Option<Phase> {
+        self.timeline.as_ref().and_then(|t| t.phase())
+    }
+
+    /// Takes the phase transitions since the last call, oldest first.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = Event> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Size of the particle pool.
+    pub fn capacity(&self) -> usize {
+        self.max_particles
+    }
+
+    /// Spawns `count` sparks at once, as far as the pool allows.
+    pub fn burst(&mut self, count: usize) {
+        let free = self.max_particles - self.particles.len();
+        for _ in 0..count.min(free) {
+            let particle = self.emitter.spawn(&mut self.rng);
+            self.particles
+                .push(particle.with_trail_length(self.trail.length));
+        }
+    }
 
-        Self { particles }
+    /// Advances one step and returns the segments it swept.
+    pub fn update(&mut self) -> FrameData {
+        self.step();
+        self.frame(1.0)
     }
 
-    pub fn update(&mut self) -> (Vec<f32>, Vec<f32>, Vec<u16>) {
-        let previous = self.particles.clone();
+    /// Advances the simulation by `TIME_DELTA`.
+    pub fn step(&mut self) {
+        if let Some(timeline) = self.timeline.as_mut() {
+            self.events.extend(timeline.advance(TIME_DELTA));
+            match timeline.params() {
+                Some(params) => {
+                    self.emitter.rate = params.rate;
+                    self.emitter.speed = params.speed;
+                    self.emitter.temperature = params.temperature;
+                    self.branching.rate = params.branching;
+                }
+                // burnt out
+                None => {
+                    self.emitter.rate = 0.0;
+                    self.branching.rate = 0.0;
+                }
+            }
+        }
 
-        let l = self.particles.len();
-        for i in 0..l {
-            self.particles[i].update();
+        if let Some(hinotama) = self.hinotama.as_mut() {
+            hinotama.step(TIME_DELTA);
+            self.emitter.position = hinotama.position;
+            self.emitter.radius = hinotama.radius();
+            // a dropped ball is the end of the sparkler
+            if !hinotama.is_attached() {
+                self.emitter.rate = 0.0;
+            }
         }
-        let (prev_vertices, prev_colors) = Self::particles_to_vertices(&previous);
-        let (current_vertices, current_colors) = Self::particles_to_vertices(&self.particles);
 
-        let vertices = [prev_vertices, current_vertices].concat();
-        let colors = [prev_colors, current_colors].concat();
+        let count = self.emitter.emit(TIME_DELTA);
+        self.burst(count);
 
-        let vertex_count = vertices.len() / 3;
-        let links: Vec<u16> = zip(0..(vertex_count / 2), (vertex_count / 2)..vertex_count)
-            .into_iter()
-            .flat_map(|s| vec![s.0 as u16, s.1 as u16])
-            .collect::<Vec<u16>>();
+        for particle in self.particles.iter_mut() {
+            particle.update(&self.physics);
+        }
 
-        (vertices, colors, links)
+        let bounds = self.bounds;
+        self.particles.retain(|p| {
+            p.age < p.lifetime
+                && p.temperature >= VISIBLE_TEMPERATURE
+                && p.position.iter().all(|x| x.abs() <= bounds)
+        });
+
+        self.branch();
     }
 
-    fn particles_to_vertices(particles: &Vec<Particle>) -> (Vec<f32>, Vec<f32>) {
-        let mut vertices = vec![];
-        let mut colors = vec![];
+    /// Splits sparks at random; a parent is replaced by its first child, and
+    /// the others are appended while the pool has room.
+    fn branch(&mut self) {
+        if self.branching.rate <= 0.0 {
+            return;
+        }
+        let probability = self.branching.probability(TIME_DELTA);
+        // children split from the next step on
+        for i in 0..self.particles.len() {
+            if !self.branching.can_split(&self.particles[i]) || self.rng.next_f32() >= probability {
+                continue;
+            }
+            let (min, max) = self.branching.children();
+            let count = self.rng.gen_usize(min, max);
+            let free = self.max_particles - self.particles.len();
+            if count - 1 > free {
+                continue;
+            }
+            let mut children = self
+                .branching
+                .split(&self.particles[i], count, &mut self.rng);
+            self.particles[i] = children.swap_remove(0);
+            self.particles.extend(children);
+        }
+    }
 
-        for particle in particles {
-            vertices.extend(particle.position.clone());
-            colors.extend(particle.color.clone());
+    /// Sparks for the instanced renderer, interpolated by `alpha` like
+    /// `frame`; the trails shrink to the last step, and the hinotama is left
+    /// out.
+    pub fn instances(&self, alpha: f32) -> Instances {
+        let mut instances = Instances::with_capacity(self.particles.len());
+        for p in self.particles.iter() {
+            let head = self.trail.point(&p.trail, p.trail_point(), 0, alpha);
+            let previous = self.trail.point(&p.trail, p.trail_point(), 1, alpha);
+            instances.push(head.position, previous.position, head.temperature, p.radius);
         }
+        instances
+    }
+
+    /// Trails up to the current state, interpolated by `alpha` in [0, 1]
+    /// between the state before the last step (0) and the current state (1).
+    ///
+    /// Vertices come in `length + 1` blocks of one per spark, from the end of
+    /// the trails to the heads, and each trail is a strip of segments joining
+    /// its vertices in consecutive blocks.
+    pub fn frame(&self, alpha: f32) -> FrameData {
+        let n = self.particles.len();
+        let length = self.trail.length;
+        let vertex_count = n * (length + 1);
 
-        (vertices, colors)
+        let mut positions = Vec::with_capacity(3 * vertex_count);
+        let mut colors = Vec::with_capacity(4 * vertex_count);
+        let mut sizes = Vec::with_capacity(vertex_count);
+        for k in (0..=length).rev() {
+            for p in self.particles.iter() {
+                let point = self.trail.point(&p.trail, p.trail_point(), k, alpha);
+                positions.extend(point.position);
+                colors.extend(self.trail.color(point.temperature, k));
+                sizes.push(p.radius);
+            }
+        }
+
+        let mut segments = Indices::with_capacity(2 * n * length, vertex_count);
+        for i in 0..n {
+            for block in 0..length {
+                segments.push((block * n + i) as u32);
+                segments.push(((block + 1) * n + i) as u32);
+            }
+        }
+
+        let mut frame = FrameData {
+            positions,
+            colors,
+            sizes,
+            segments,
+            discs: vec![],
+        };
+        if let Some(hinotama) = self.hinotama.as_ref() {
+            frame.extend(&hinotama.frame(alpha));
+        }
+        frame
     }
 }
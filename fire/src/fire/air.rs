@@ -0,0 +1,45 @@
+//! Still air at room temperature, and the drag it puts on a sphere.
+use std::f32::consts;
+
+pub const TEMPERATURE: f32 = 293.0; // [K]
+pub const DENSITY: f32 = 1.2; // [kg/m^3]
+pub const VISCOSITY: f32 = 1.5e-5; // kinematic [m^2/s]
+pub const CONDUCTIVITY: f32 = 0.026; // [W/(m K)]
+pub const PRANDTL: f32 = 0.71;
+
+/// Reynolds number of a sphere moving at `speed` [m/s].
+pub fn reynolds(radius: f32, speed: f32) -> f32 {
+    speed * 2.0 * radius / VISCOSITY
+}
+
+/// Drag coefficient of a sphere by the Schiller–Naumann correlation; Stokes
+/// drag at low Reynolds numbers, leveling off to Newton's 0.44.
+/// ref: https://en.wikipedia.org/wiki/Drag_coefficient
+pub fn drag_coefficient(reynolds: f32) -> f32 {
+    if reynolds <= 0.0 {
+        return f32::INFINITY;
+    }
+    if reynolds < 1000.0 {
+        24.0 / reynolds * (1.0 + 0.15 * libm::powf(reynolds, 0.687))
+    } else {
+        0.44
+    }
+}
+
+/// Drag on a sphere moving at `speed` [m/s] [N], against its motion.
+pub fn drag(radius: f32, speed: f32) -> f32 {
+    drag_per_speed(radius, speed) * speed
+}
+
+/// Drag over speed [N s/m]; finite at rest, where it is Stokes' 6 pi mu r.
+pub fn drag_per_speed(radius: f32, speed: f32) -> f32 {
+    let area = consts::PI * radius.powi(2);
+    let re = reynolds(radius, speed);
+    // the drag coefficient times the speed, as 24 / Re diverges at rest
+    let cd_speed = if re < 1000.0 {
+        24.0 * VISCOSITY / (2.0 * radius) * (1.0 + 0.15 * libm::powf(re, 0.687))
+    } else {
+        0.44 * speed
+    };
+    0.5 * DENSITY * area * cd_speed
+}
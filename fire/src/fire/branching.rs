@@ -0,0 +1,142 @@
+use super::particle::Particle;
+use super::thermal;
+use crate::rng::Pcg32;
+
+/// Splitting of sparks mid-flight, which gives matsuba its pine needles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branching {
+    /// chance of a spark splitting, per second of flight
+    pub rate: f32,
+    // range of children per split, inclusive; set through `with_children`,
+    // which keeps it at 2 or more
+    children: (usize, usize),
+    /// largest angle between a child and its parent [rad]
+    pub spread: f32,
+    /// generations a spark may split for
+    pub max_depth: u8,
+}
+
+impl Default for Branching {
+    fn default() -> Self {
+        Self {
+            rate: 0.0,
+            children: (2, 3),
+            spread: 0.6,
+            max_depth: 3,
+        }
+    }
+}
+
+impl Branching {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_children(mut self, min: usize, max: usize) -> Self {
+        assert!(
+            2 <= min && min <= max,
+            "it requires; 2 <= min <= max\n\
+            min must be grater than 1 and no more than max,\n\
+            but got min: {min}, max: {max}"
+        );
+        self.children = (min, max);
+        self
+    }
+
+    /// Range of children per split, inclusive.
+    pub fn children(&self) -> (usize, usize) {
+        self.children
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: u8) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Chance of a split within `dt` [s].
+    pub fn probability(&self, dt: f32) -> f32 {
+        1.0 - libm::expf(-self.rate.max(0.0) * dt)
+    }
+
+    pub fn can_split(&self, particle: &Particle) -> bool {
+        particle.generation < self.max_depth
+    }
+
+    /// Splits `parent` into `count` children.
+    ///
+    /// Children share the parent's position and trail, so their trails
+    /// continue from the parent's. Each takes an equal share of the mass and
+    /// is kicked sideways by an angle of up to `spread`, less the mean kick,
+    /// so that the total momentum stays the parent's.
+    pub fn split(&self, parent: &Particle, count: usize, rng: &mut Pcg32) -> Vec<Particle> {
+        let v = parent.velocity;
+        let speed = dot(v, v).sqrt();
+        let (u, w) = perpendicular_basis(v);
+
+        let kicks = (0..count)
+            .map(|_| {
+                let angle = rng.gen_range(0.0, self.spread);
+                let around = rng.gen_range(0.0, 2.0 * std::f32::consts::PI);
+                let size = speed * libm::tanf(angle);
+                let (s, c) = (libm::sinf(around) * size, libm::cosf(around) * size);
+                [0, 1, 2].map(|i| u[i] * c + w[i] * s)
+            })
+            .collect::<Vec<_>>();
+        let mean = [0, 1, 2].map(|i| kicks.iter().map(|k| k[i]).sum::<f32>() / count as f32);
+
+        kicks
+            .iter()
+            .map(|kick| Particle {
+                velocity: [0, 1, 2].map(|i| v[i] + kick[i] - mean[i]),
+                mass: parent.mass / count as f32,
+                radius: thermal::radius(parent.mass / count as f32, parent.density),
+                burnt: parent.burnt / count as f32,
+                generation: parent.generation + 1,
+                ..parent.clone()
+            })
+            .collect()
+    }
+}
+
+/// Two unit vectors perpendicular to `v` and to each other.
+fn perpendicular_basis(v: [f32; 3]) -> ([f32; 3], [f32; 3]) {
+    // cross with the axis least aligned with v
+    let axis = if v[0].abs() <= v[1].abs() && v[0].abs() <= v[2].abs() {
+        [1.0, 0.0, 0.0]
+    } else if v[1].abs() <= v[2].abs() {
+        [0.0, 1.0, 0.0]
+    } else {
+        [0.0, 0.0, 1.0]
+    };
+    let u = normalize(cross(v, axis));
+    let w = normalize(cross(v, u));
+    (u, w)
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = dot(v, v).sqrt();
+    if length == 0.0 {
+        return [0.0; 3];
+    }
+    v.map(|x| x / length)
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
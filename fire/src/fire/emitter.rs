@@ -0,0 +1,113 @@
+use std::f32::consts;
+
+use super::particle::Particle;
+use super::{KELVIN, MAX_SPEED};
+use crate::rng::Pcg32;
+
+/// Spawns sparks continuously from the surface of a sphere.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    /// center [m]
+    pub position: [f32; 3],
+    /// [m]; 0 is a point
+    pub radius: f32,
+    /// sparks per second
+    pub rate: f32,
+    /// range of spark lifetimes [s]
+    pub lifetime: (f32, f32),
+    /// range of launch speeds [m/s]
+    pub speed: (f32, f32),
+    /// range of launch temperatures [K]
+    pub temperature: (f32, f32),
+    /// range of spark radii [m]
+    pub spark_radius: (f32, f32),
+    // fraction of a spark carried over between steps
+    accumulator: f32,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self::new([0.0, 0.5, 0.0])
+    }
+}
+
+impl Emitter {
+    pub fn new(position: [f32; 3]) -> Self {
+        Self {
+            position,
+            radius: 0.0,
+            rate: 200.0,
+            lifetime: (1.0, 2.0),
+            speed: (0.0, MAX_SPEED),
+            temperature: (800.0 + KELVIN, 1000.0 + KELVIN),
+            spark_radius: (0.3e-3, 0.8e-3),
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    pub fn with_speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+
+    pub fn with_temperature(mut self, min: f32, max: f32) -> Self {
+        self.temperature = (min, max);
+        self
+    }
+
+    pub fn with_spark_radius(mut self, min: f32, max: f32) -> Self {
+        self.spark_radius = (min, max);
+        self
+    }
+
+    /// Number of sparks due over `dt` [s].
+    pub fn emit(&mut self, dt: f32) -> usize {
+        self.accumulator += self.rate.max(0.0) * dt;
+        let count = self.accumulator.floor();
+        self.accumulator -= count;
+        count as usize
+    }
+
+    /// A spark launched from the surface, straight out in a random direction.
+    pub fn spawn(&self, rng: &mut Pcg32) -> Particle {
+        let lifetime = rng.gen_range(self.lifetime.0, self.lifetime.1);
+        let speed = rng.gen_range(self.speed.0, self.speed.1);
+        let direction = Self::init_velocity(
+            1.0,
+            rng.gen_range(0.0, 2.0 * consts::PI),
+            rng.gen_range(0.0, 2.0 * consts::PI),
+        );
+        let temperature = rng.gen_range(self.temperature.0, self.temperature.1);
+        let radius = rng.gen_range(self.spark_radius.0, self.spark_radius.1);
+        let position = [0, 1, 2].map(|i| self.position[i] + direction[i] * self.radius);
+        let velocity = direction.map(|d| d * speed);
+        Particle::new(position, velocity, temperature, lifetime).with_radius(radius)
+    }
+
+    fn init_velocity(velocity: f32, latitude: f32, longitude: f32) -> [f32; 3] {
+        // libm rather than std, so that the same seed gives the same bits on
+        // every target
+        let (sin_lat, cos_lat) = (libm::sinf(latitude), libm::cosf(latitude));
+        let (sin_lon, cos_lon) = (libm::sinf(longitude), libm::cosf(longitude));
+        [
+            cos_lat * sin_lon * velocity,
+            cos_lat * cos_lon * velocity,
+            sin_lat * velocity,
+        ]
+    }
+}
@@ -0,0 +1,140 @@
+use super::{thermal, GRAVITY};
+use crate::frame::{Disc, FrameData};
+use crate::palette::Palette;
+
+/// The molten ball hanging at the tip of the paper twist.
+///
+/// It hangs from the tip on a damped spring, so it wobbles when shaken and
+/// settles back, and it slowly grows as the powder melts into it. Once the
+/// spring tension exceeds what the melt can hold, it drops and falls freely.
+#[derive(Debug, Clone)]
+pub struct Hinotama {
+    /// tip of the paper twist the ball hangs from [m]
+    pub anchor: [f32; 3],
+    /// center [m]
+    pub position: [f32; 3],
+    /// [m/s]
+    pub velocity: [f32; 3],
+    /// [kg]
+    pub mass: f32,
+    /// mass melting in per second [kg/s]
+    pub growth: f32,
+    /// [K]
+    pub temperature: f32,
+    /// spring constant of the hold [N/m]
+    pub stiffness: f32,
+    /// velocity damping of the wobble [1/s]
+    pub damping: f32,
+    /// tension at which the ball drops [N]
+    pub max_tension: f32,
+    previous: [f32; 3],
+    attached: bool,
+}
+
+impl Default for Hinotama {
+    fn default() -> Self {
+        Self::new([0.0, 0.5, 0.0])
+    }
+}
+
+impl Hinotama {
+    pub fn new(anchor: [f32; 3]) -> Self {
+        Self {
+            anchor,
+            position: anchor,
+            velocity: [0.0; 3],
+            mass: 2.0e-5,
+            growth: 1.0e-6,
+            temperature: 1500.0,
+            stiffness: 1.0e-2,
+            damping: 4.0,
+            max_tension: 1.5e-3,
+            previous: anchor,
+            attached: true,
+        }
+    }
+
+    pub fn with_mass(mut self, mass: f32, growth: f32) -> Self {
+        self.mass = mass;
+        self.growth = growth;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn with_spring(mut self, stiffness: f32, damping: f32) -> Self {
+        self.stiffness = stiffness;
+        self.damping = damping;
+        self
+    }
+
+    pub fn with_max_tension(mut self, max_tension: f32) -> Self {
+        self.max_tension = max_tension;
+        self
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.attached
+    }
+
+    /// Radius of a sphere of the mass [m].
+    pub fn radius(&self) -> f32 {
+        thermal::radius(self.mass, thermal::DENSITY)
+    }
+
+    /// Pull of the spring on the ball [N], towards the anchor.
+    pub fn tension(&self) -> f32 {
+        let stretch = [0, 1, 2].map(|i| self.position[i] - self.anchor[i]);
+        self.stiffness * stretch.iter().map(|x| x * x).sum::<f32>().sqrt()
+    }
+
+    /// A jerk of the hand; changes the velocity by `impulse` [N s].
+    pub fn shake(&mut self, impulse: [f32; 3]) {
+        self.velocity = [0, 1, 2].map(|i| self.velocity[i] + impulse[i] / self.mass);
+    }
+
+    /// Advances `dt` [s] with semi-implicit Euler.
+    pub fn step(&mut self, dt: f32) {
+        self.previous = self.position;
+
+        let gravity = [0.0, -self.mass * GRAVITY, 0.0];
+        let force = if self.attached {
+            let spring = [0, 1, 2].map(|i| {
+                -self.stiffness * (self.position[i] - self.anchor[i])
+                    - self.damping * self.mass * self.velocity[i]
+            });
+            self.mass += self.growth * dt;
+            [0, 1, 2].map(|i| gravity[i] + spring[i])
+        } else {
+            gravity
+        };
+
+        self.velocity = [0, 1, 2].map(|i| self.velocity[i] + force[i] / self.mass * dt);
+        self.position = [0, 1, 2].map(|i| self.position[i] + self.velocity[i] * dt);
+
+        if self.attached && self.tension() > self.max_tension {
+            self.attached = false;
+        }
+    }
+
+    /// Disc of the ball glowing with its black body color, at `alpha`
+    /// between the last two steps.
+    pub fn frame(&self, alpha: f32) -> FrameData {
+        let center =
+            [0, 1, 2].map(|i| self.previous[i] + (self.position[i] - self.previous[i]) * alpha);
+        let palette = Palette::black_body();
+        let brightness = palette.brightness(self.temperature);
+        let color = palette.color(self.temperature).map(|c| c * brightness);
+        FrameData {
+            discs: vec![Disc {
+                center,
+                color: [color[0], color[1], color[2], 1.0],
+                radius: self.radius(),
+            }],
+            ..FrameData::default()
+        }
+    }
+}
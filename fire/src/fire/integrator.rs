@@ -0,0 +1,92 @@
+//! Numerical schemes advancing a particle's position and velocity.
+use std::fmt::Debug;
+
+/// Acceleration [m/s^2] at a position and velocity.
+pub type Acceleration<'a> = dyn Fn([f32; 3], [f32; 3]) -> [f32; 3] + 'a;
+
+pub trait Integrator: Debug + Send + Sync {
+    /// Advances `position` and `velocity` by `dt` [s].
+    fn step(
+        &self,
+        position: &mut [f32; 3],
+        velocity: &mut [f32; 3],
+        acceleration: &Acceleration,
+        dt: f32,
+    );
+}
+
+/// Velocity first, then position with the new velocity; first order, but
+/// symplectic and as cheap as explicit Euler.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step(
+        &self,
+        position: &mut [f32; 3],
+        velocity: &mut [f32; 3],
+        acceleration: &Acceleration,
+        dt: f32,
+    ) {
+        let a = acceleration(*position, *velocity);
+        *velocity = add(*velocity, a, dt);
+        *position = add(*position, *velocity, dt);
+    }
+}
+
+/// Velocity Verlet; second order. The acceleration at the end of the step
+/// depends on the velocity through drag, so it is taken at an Euler
+/// prediction of the velocity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn step(
+        &self,
+        position: &mut [f32; 3],
+        velocity: &mut [f32; 3],
+        acceleration: &Acceleration,
+        dt: f32,
+    ) {
+        let a0 = acceleration(*position, *velocity);
+        *position = add(add(*position, *velocity, dt), a0, 0.5 * dt * dt);
+        let predicted = add(*velocity, a0, dt);
+        let a1 = acceleration(*position, predicted);
+        *velocity = add(*velocity, add(a0, a1, 1.0), 0.5 * dt);
+    }
+}
+
+/// Classic fourth order Runge–Kutta.
+/// ref: https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rk4;
+
+impl Integrator for Rk4 {
+    fn step(
+        &self,
+        position: &mut [f32; 3],
+        velocity: &mut [f32; 3],
+        acceleration: &Acceleration,
+        dt: f32,
+    ) {
+        let (x, v) = (*position, *velocity);
+        let (k1x, k1v) = (v, acceleration(x, v));
+        let (x2, v2) = (add(x, k1x, 0.5 * dt), add(v, k1v, 0.5 * dt));
+        let (k2x, k2v) = (v2, acceleration(x2, v2));
+        let (x3, v3) = (add(x, k2x, 0.5 * dt), add(v, k2v, 0.5 * dt));
+        let (k3x, k3v) = (v3, acceleration(x3, v3));
+        let (x4, v4) = (add(x, k3x, dt), add(v, k3v, dt));
+        let (k4x, k4v) = (v4, acceleration(x4, v4));
+
+        let weighted = |k1: [f32; 3], k2: [f32; 3], k3: [f32; 3], k4: [f32; 3]| {
+            [0, 1, 2].map(|i| (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]) / 6.0)
+        };
+        *position = add(x, weighted(k1x, k2x, k3x, k4x), dt);
+        *velocity = add(v, weighted(k1v, k2v, k3v, k4v), dt);
+    }
+}
+
+// a + b * scale
+fn add(a: [f32; 3], b: [f32; 3], scale: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + b[i] * scale)
+}
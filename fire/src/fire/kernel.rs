@@ -0,0 +1,292 @@
+//! The simulation step as the GPU runs it in `simulate.glsl`: a fixed pool
+//! of slots updated independently, emission scheduled per slot, and random
+//! numbers hashed from the slot and the step instead of drawn from a shared
+//! generator. This is the reference the shader is written against, line by
+//! line; physics is that of `Particle` with semi-implicit Euler, no
+//! oxidation and no branching.
+use std::f32::consts;
+
+use super::emitter::Emitter;
+use super::particle::{EMISSIVITY, HEAT_CAPACITY, MAX_DRAG_DECAY, MAX_TRAVEL};
+use super::{air, thermal, FireConfig, GRAVITY, TIME_DELTA, VISIBLE_TEMPERATURE};
+use crate::frame::Instances;
+
+/// Floats per slot in the simulation buffers, in the order of the fields of
+/// `KernelParticle`; the first 8 are an instance, with the temperature and
+/// radius as full floats.
+pub const PARTICLE_FLOATS: usize = 13;
+
+/// One slot of the pool; dead slots are set to 0 K, and wait for their turn
+/// to respawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct KernelParticle {
+    pub position: [f32; 3],
+    /// position before the last step
+    pub previous: [f32; 3],
+    /// [K]
+    pub temperature: f32,
+    /// [m]
+    pub radius: f32,
+    pub velocity: [f32; 3],
+    /// time since the slot's last scheduled spawn [s]
+    pub age: f32,
+    /// [s]
+    pub lifetime: f32,
+}
+
+impl KernelParticle {
+    pub fn is_alive(&self) -> bool {
+        self.temperature >= VISIBLE_TEMPERATURE
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.velocity.iter().map(|v| v * v).sum::<f32>().sqrt()
+    }
+
+    pub fn floats(&self) -> [f32; PARTICLE_FLOATS] {
+        let [x, y, z] = self.position;
+        let [px, py, pz] = self.previous;
+        let [vx, vy, vz] = self.velocity;
+        [
+            x,
+            y,
+            z,
+            px,
+            py,
+            pz,
+            self.temperature,
+            self.radius,
+            vx,
+            vy,
+            vz,
+            self.age,
+            self.lifetime,
+        ]
+    }
+}
+
+/// PCG hash of a word; a slot has no generator to carry between steps, so
+/// its random numbers are hashed afresh every step.
+/// ref: https://jcgt.org/published/0009/03/02/
+pub fn pcg_hash(x: u32) -> u32 {
+    let state = x.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277_803_737);
+    (word >> 22) ^ word
+}
+
+/// Random numbers of one slot in one step.
+struct SlotRng(u32);
+
+impl SlotRng {
+    fn new(seed: u32, frame: u32, index: u32) -> Self {
+        Self(pcg_hash(
+            index.wrapping_add(pcg_hash(frame.wrapping_add(pcg_hash(seed)))),
+        ))
+    }
+
+    // 24 bits, exact in a float on both sides
+    fn next_f32(&mut self) -> f32 {
+        self.0 = pcg_hash(self.0);
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+
+    fn gen_range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+/// Uniforms of the simulation kernel.
+#[derive(Debug, Clone)]
+pub struct Kernel {
+    pub emitter: Emitter,
+    pub bounds: f32,
+    pub max_substeps: usize,
+    /// [m^2/s], as in `Physics`
+    pub burn_rate: f32,
+    pub seed: u32,
+    /// time between spawns of a slot [s]; 0 never spawns
+    pub period: f32,
+    /// steps run so far
+    pub frame: u32,
+}
+
+impl Kernel {
+    /// A kernel spawning `config.emitter.rate` sparks per second from
+    /// `capacity` slots; each slot spawns every `capacity / rate` seconds,
+    /// replacing its spark if that still lives.
+    pub fn new(config: &FireConfig, capacity: usize) -> Self {
+        let rate = config.emitter.rate;
+        Self {
+            emitter: config.emitter.clone(),
+            bounds: config.bounds,
+            max_substeps: config.physics.max_substeps,
+            burn_rate: config.physics.burn_rate,
+            seed: config.seed as u32,
+            period: if rate > 0.0 {
+                capacity as f32 / rate
+            } else {
+                0.0
+            },
+            frame: 0,
+        }
+    }
+
+    /// Empty slots, staggered to spawn at the emitter's rate from the first
+    /// step on.
+    pub fn initial(&self, capacity: usize) -> Vec<KernelParticle> {
+        (0..capacity)
+            .map(|i| KernelParticle {
+                age: self.period * (1.0 - i as f32 / capacity as f32),
+                ..KernelParticle::default()
+            })
+            .collect()
+    }
+
+    /// Counts a step run elsewhere, on the GPU.
+    pub fn advance(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// Advances slot `index` by `TIME_DELTA`, as `simulate.glsl` does.
+    pub fn step(&self, index: u32, p: &mut KernelParticle) {
+        let mut rng = SlotRng::new(self.seed, self.frame, index);
+        if self.period > 0.0 && p.age >= self.period {
+            let age = p.age - self.period;
+            *p = self.spawn(&mut rng);
+            p.age = age;
+        }
+        if !p.is_alive() {
+            p.age += TIME_DELTA;
+            return;
+        }
+
+        p.previous = p.position;
+        let mass = thermal::mass(p.radius, thermal::DENSITY);
+        let substeps = self.substeps(p, mass);
+        let dt = TIME_DELTA / substeps as f32;
+        for _ in 0..substeps {
+            let k = air::drag_per_speed(p.radius, p.speed()) / mass;
+            let v = p.velocity;
+            p.velocity = [
+                v[0] - k * v[0] * dt,
+                v[1] - (k * v[1] + GRAVITY) * dt,
+                v[2] - k * v[2] * dt,
+            ];
+            for (x, v) in p.position.iter_mut().zip(p.velocity) {
+                *x += v * dt;
+            }
+        }
+
+        let power = -thermal::radiation(p.radius, EMISSIVITY, p.temperature)
+            - thermal::convection(p.radius, p.speed(), p.temperature);
+        p.radius = thermal::burn_back(p.radius, self.burn_rate, TIME_DELTA);
+        let mass = thermal::mass(p.radius, thermal::DENSITY);
+        p.temperature = if mass > 0.0 {
+            (p.temperature + power / (mass * HEAT_CAPACITY) * TIME_DELTA).max(air::TEMPERATURE)
+        } else {
+            air::TEMPERATURE
+        };
+        p.age += TIME_DELTA;
+
+        let bounds = self.bounds;
+        if p.age >= p.lifetime
+            || p.temperature < VISIBLE_TEMPERATURE
+            || p.position.iter().any(|x| x.abs() > bounds)
+        {
+            p.temperature = 0.0;
+        }
+    }
+
+    fn substeps(&self, p: &KernelParticle, mass: f32) -> usize {
+        let travel = p.speed() * TIME_DELTA / MAX_TRAVEL;
+        let decay = air::drag_per_speed(p.radius, p.speed()) / mass * TIME_DELTA / MAX_DRAG_DECAY;
+        (travel.max(decay).ceil() as usize).clamp(1, self.max_substeps)
+    }
+
+    // the draws of `Emitter::spawn`, in its order
+    fn spawn(&self, rng: &mut SlotRng) -> KernelParticle {
+        let emitter = &self.emitter;
+        let lifetime = rng.gen_range(emitter.lifetime);
+        let speed = rng.gen_range(emitter.speed);
+        let latitude = rng.gen_range((0.0, 2.0 * consts::PI));
+        let longitude = rng.gen_range((0.0, 2.0 * consts::PI));
+        let temperature = rng.gen_range(emitter.temperature);
+        let radius = rng.gen_range(emitter.spark_radius);
+
+        let (sin_lat, cos_lat) = (libm::sinf(latitude), libm::cosf(latitude));
+        let (sin_lon, cos_lon) = (libm::sinf(longitude), libm::cosf(longitude));
+        let direction = [cos_lat * sin_lon, cos_lat * cos_lon, sin_lat];
+        let position = [0, 1, 2].map(|i| emitter.position[i] + direction[i] * emitter.radius);
+        KernelParticle {
+            position,
+            previous: position,
+            temperature,
+            radius,
+            velocity: direction.map(|d| d * speed),
+            age: 0.0,
+            lifetime,
+        }
+    }
+}
+
+/// The kernel run over the pool on the CPU; the reference of the GPU path,
+/// and its fallback.
+#[derive(Debug, Clone)]
+pub struct KernelSimulation {
+    pub kernel: Kernel,
+    /// the slots; on a GPU they stay as uploaded, and the state lives there
+    pub particles: Vec<KernelParticle>,
+}
+
+impl KernelSimulation {
+    pub fn new(config: &FireConfig) -> Self {
+        assert!(
+            config.max_particles > 0,
+            "it requires; max_particles > 0\n\
+            max_particles must be grater than 0,\n\
+            but got {}",
+            config.max_particles
+        );
+        let kernel = Kernel::new(config, config.max_particles);
+        Self {
+            particles: kernel.initial(config.max_particles),
+            kernel,
+        }
+    }
+
+    /// Size of the pool.
+    pub fn capacity(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Advances every slot by `TIME_DELTA`.
+    pub fn step(&mut self) {
+        for (i, p) in self.particles.iter_mut().enumerate() {
+            self.kernel.step(i as u32, p);
+        }
+        self.kernel.advance();
+    }
+
+    pub fn alive(&self) -> impl Iterator<Item = &KernelParticle> {
+        self.particles.iter().filter(|p| p.is_alive())
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.alive().count()
+    }
+
+    /// Live sparks, from their previous position to the current one.
+    pub fn instances(&self) -> Instances {
+        let mut instances = Instances::with_capacity(self.particle_count());
+        for p in self.alive() {
+            instances.push(p.position, p.previous, p.temperature, p.radius);
+        }
+        instances
+    }
+
+    /// The slots as uploaded to the simulation buffers.
+    pub fn data(&self) -> Vec<f32> {
+        self.particles.iter().flat_map(|p| p.floats()).collect()
+    }
+}
@@ -0,0 +1,162 @@
+use super::physics::Physics;
+use super::thermal;
+use super::trail::{Trail, TrailPoint};
+use super::{air, GRAVITY, TIME_DELTA};
+
+const RADIUS: f32 = 0.5e-3; // [m]
+pub(super) const HEAT_CAPACITY: f32 = 1000.0; // [J/(kg K)]
+pub(super) const EMISSIVITY: f32 = 0.9;
+
+// substeps keep each one within these
+pub(super) const MAX_TRAVEL: f32 = 0.1; // [m]
+pub(super) const MAX_DRAG_DECAY: f32 = 0.5; // drag rate times substep
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub position: [f32; 3],
+    pub velocity: [f32; 3],
+    /// [K]
+    pub temperature: f32,
+    /// [kg]
+    pub mass: f32,
+    /// [m]
+    pub radius: f32,
+    /// [kg/m^3]
+    pub density: f32,
+    /// specific heat [J/(kg K)]
+    pub heat_capacity: f32,
+    pub emissivity: f32,
+    /// fuel oxidized so far [kg]
+    pub burnt: f32,
+    /// time since the spark left the emitter [s]
+    pub age: f32,
+    /// age at which the spark burns out [s]
+    pub lifetime: f32,
+    /// number of splits since leaving the emitter
+    pub generation: u8,
+    /// past states, newest first; one more than the drawn trail, to
+    /// interpolate between steps
+    pub trail: Trail,
+}
+
+impl Particle {
+    pub fn new(position: [f32; 3], velocity: [f32; 3], temperature: f32, lifetime: f32) -> Self {
+        Self {
+            position,
+            velocity,
+            temperature,
+            age: 0.0,
+            lifetime,
+            mass: thermal::mass(RADIUS, thermal::DENSITY),
+            radius: RADIUS,
+            density: thermal::DENSITY,
+            heat_capacity: HEAT_CAPACITY,
+            emissivity: EMISSIVITY,
+            burnt: 0.0,
+            generation: 0,
+            trail: Trail::new(
+                TrailPoint {
+                    position,
+                    temperature,
+                },
+                2,
+            ),
+        }
+    }
+
+    /// Resizes the spark, keeping its density.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self.mass = thermal::mass(radius, self.density);
+        self
+    }
+
+    /// Changes the material, keeping the size.
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self.mass = thermal::mass(self.radius, density);
+        self
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.velocity.iter().map(|v| v * v).sum::<f32>().sqrt()
+    }
+
+    /// Keeps `length` segments of trail.
+    pub fn with_trail_length(mut self, length: usize) -> Self {
+        self.trail = Trail::new(self.trail_point(), length + 1);
+        self
+    }
+
+    pub fn trail_point(&self) -> TrailPoint {
+        TrailPoint {
+            position: self.position,
+            temperature: self.temperature,
+        }
+    }
+
+    /// Advances `TIME_DELTA`.
+    pub fn update(&mut self, physics: &Physics) {
+        self.trail.push(self.trail_point());
+        self.update_motion(physics);
+        self.update_temperature(physics);
+        self.age += TIME_DELTA;
+    }
+
+    /// Substeps needed this step, so that a fast spark does not skip across
+    /// the air and the drag of a tiny one does not blow up.
+    pub fn substeps(&self, max_substeps: usize) -> usize {
+        let travel = self.speed() * TIME_DELTA / MAX_TRAVEL;
+        let decay = air::drag_per_speed(self.radius, self.speed()) / self.mass * TIME_DELTA
+            / MAX_DRAG_DECAY;
+        (travel.max(decay).ceil() as usize).clamp(1, max_substeps)
+    }
+
+    fn update_motion(&mut self, physics: &Physics) {
+        // quadratic in the speed, with a Reynolds dependent coefficient, so
+        // small sparks brake much harder than big droplets
+        let (radius, mass) = (self.radius, self.mass);
+        let acceleration = |_: [f32; 3], v: [f32; 3]| {
+            let speed = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            let k = air::drag_per_speed(radius, speed) / mass;
+            [-k * v[0], -k * v[1] - GRAVITY, -k * v[2]]
+        };
+
+        let substeps = self.substeps(physics.max_substeps);
+        let dt = TIME_DELTA / substeps as f32;
+        for _ in 0..substeps {
+            physics
+                .integrator
+                .step(&mut self.position, &mut self.velocity, &acceleration, dt);
+        }
+    }
+
+    fn update_temperature(&mut self, physics: &Physics) {
+        let (r, t) = (self.radius, self.temperature);
+        let mut power =
+            -thermal::radiation(r, self.emissivity, t) - thermal::convection(r, self.speed(), t);
+        if let Some(oxidation) = physics.oxidation.as_ref() {
+            let burn = oxidation.burn(self.mass, self.burnt, self.age, TIME_DELTA);
+            self.burnt += burn;
+            power += burn * oxidation.heat / TIME_DELTA;
+            // the fuel leaves as gas, and the spark shrinks
+            self.mass -= burn;
+            self.radius = thermal::radius(self.mass, self.density);
+        }
+        // the surface burns back steadily, flare or not
+        if physics.burn_rate > 0.0 {
+            let mass = self.mass;
+            self.radius = thermal::burn_back(self.radius, physics.burn_rate, TIME_DELTA);
+            self.mass = thermal::mass(self.radius, self.density);
+            self.burnt += mass - self.mass;
+        }
+        if self.mass <= 0.0 {
+            // burnt away, and no longer seen
+            self.temperature = air::TEMPERATURE;
+            return;
+        }
+        self.temperature += power / (self.mass * self.heat_capacity) * TIME_DELTA;
+        // cannot cool below the air around it, even when a step overshoots
+        self.temperature = self.temperature.max(air::TEMPERATURE);
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use super::integrator::{Integrator, SemiImplicitEuler};
+use super::thermal::Oxidation;
+
+/// How sparks move and burn.
+#[derive(Debug, Clone)]
+pub struct Physics {
+    pub integrator: Arc<dyn Integrator>,
+    /// cap on substeps per step for fast or strongly braked sparks
+    pub max_substeps: usize,
+    /// fall of the square of a spark's diameter as its surface burns
+    /// [m^2/s]; 0 keeps the size
+    pub burn_rate: f32,
+    /// late flare of sparks burning their fuel; `None` only cools
+    pub oxidation: Option<Oxidation>,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self {
+            integrator: Arc::new(SemiImplicitEuler),
+            max_substeps: 64,
+            // a 1 mm spark loses about a tenth of its diameter in 2 s
+            burn_rate: 1.0e-7,
+            oxidation: None,
+        }
+    }
+}
+
+impl Physics {
+    pub fn with_integrator<I: Integrator + 'static>(mut self, integrator: I) -> Self {
+        self.integrator = Arc::new(integrator);
+        self
+    }
+
+    pub fn with_max_substeps(mut self, max_substeps: usize) -> Self {
+        assert!(
+            max_substeps > 0,
+            "it requires; max_substeps > 0\n\
+            max_substeps must be grater than 0,\n\
+            but got {max_substeps}"
+        );
+        self.max_substeps = max_substeps;
+        self
+    }
+
+    pub fn with_burn_rate(mut self, burn_rate: f32) -> Self {
+        assert!(
+            burn_rate >= 0.0,
+            "it requires; burn_rate >= 0\n\
+            burn_rate must be grater than or equal to 0,\n\
+            but got {burn_rate}"
+        );
+        self.burn_rate = burn_rate;
+        self
+    }
+
+    pub fn with_oxidation(mut self, oxidation: Oxidation) -> Self {
+        self.oxidation = Some(oxidation);
+        self
+    }
+}
@@ -0,0 +1,84 @@
+//! Heat balance of a spark: radiative and convective cooling, and optional
+//! heating by oxidation of its remaining fuel.
+use std::f32::consts;
+
+use super::air;
+
+const STEFAN_BOLTZMANN: f32 = 5.670_374e-8; // [W/(m^2 K^4)]
+
+// molten potassium nitrate, sulfur and charcoal
+pub const DENSITY: f32 = 1800.0; // [kg/m^3]
+
+/// Mass of a sphere [kg].
+pub fn mass(radius: f32, density: f32) -> f32 {
+    4.0 / 3.0 * consts::PI * radius.powi(3) * density
+}
+
+/// Radius of a sphere [m].
+pub fn radius(mass: f32, density: f32) -> f32 {
+    libm::cbrtf(3.0 * mass / (4.0 * consts::PI * density))
+}
+
+/// Radius [m] of a spark after its surface burns back for `dt` [s]; the
+/// square of the diameter falls by `burn_rate` [m^2/s], the d² law of
+/// droplet combustion, down to 0 once it has burnt away.
+pub fn burn_back(radius: f32, burn_rate: f32, dt: f32) -> f32 {
+    let diameter2 = 4.0 * radius * radius - burn_rate * dt;
+    0.5 * diameter2.max(0.0).sqrt()
+}
+
+/// Heat radiated by a sphere into the surroundings [W] (Stefan–Boltzmann).
+pub fn radiation(radius: f32, emissivity: f32, temperature: f32) -> f32 {
+    let area = 4.0 * consts::PI * radius.powi(2);
+    emissivity * STEFAN_BOLTZMANN * area * (temperature.powi(4) - air::TEMPERATURE.powi(4))
+}
+
+/// Heat carried off by air flowing past a sphere at `speed` [m/s] [W].
+///
+/// Nusselt number by the Ranz–Marshall correlation, Nu = 2 + 0.6 Re^1/2 Pr^1/3.
+/// ref: https://en.wikipedia.org/wiki/Nusselt_number
+pub fn convection(radius: f32, speed: f32, temperature: f32) -> f32 {
+    let reynolds = air::reynolds(radius, speed);
+    let nusselt = 2.0 + 0.6 * reynolds.sqrt() * libm::cbrtf(air::PRANDTL);
+    let h = nusselt * air::CONDUCTIVITY / (2.0 * radius);
+    let area = 4.0 * consts::PI * radius.powi(2);
+    h * area * (temperature - air::TEMPERATURE)
+}
+
+/// Burning of the fuel left in a spark, which makes it flare up late in its
+/// flight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Oxidation {
+    /// age at which the fuel ignites [s]
+    pub delay: f32,
+    /// share of the remaining fuel burnt per second [1/s]
+    pub rate: f32,
+    /// heat released per mass of fuel [J/kg]
+    pub heat: f32,
+    /// share of the spark's mass that is fuel
+    pub fuel: f32,
+}
+
+impl Default for Oxidation {
+    fn default() -> Self {
+        Self {
+            delay: 0.2,
+            rate: 1.0,
+            // charcoal
+            heat: 3.0e7,
+            fuel: 0.3,
+        }
+    }
+}
+
+impl Oxidation {
+    /// Fuel burnt over `dt` [s] by a spark of `mass` [kg] that has already
+    /// burnt `burnt` [kg] of its original mass, at `age` [s].
+    pub fn burn(&self, mass: f32, burnt: f32, age: f32, dt: f32) -> f32 {
+        if age < self.delay {
+            return 0.0;
+        }
+        let remaining = ((mass + burnt) * self.fuel - burnt).max(0.0);
+        remaining * (self.rate * dt).min(1.0)
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+
+/// Burn phases of a senko hanabi, in the order they appear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// bud; the molten bead forms and barely sparks
+    Tsubomi,
+    /// peony; sparse, crackling sparks
+    Botan,
+    /// pine needles; the most vigorous phase, sparks branching again and again
+    Matsuba,
+    /// willow; long thin streaks drooping down
+    Yanagi,
+    /// scattered chrysanthemum; the last petals falling as the bead cools
+    ChiriGiku,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Tsubomi => "tsubomi",
+            Self::Botan => "botan",
+            Self::Matsuba => "matsuba",
+            Self::Yanagi => "yanagi",
+            Self::ChiriGiku => "chiri-giku",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What a phase drives in the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseParams {
+    /// sparks per second
+    pub rate: f32,
+    /// range of launch speeds [m/s]
+    pub speed: (f32, f32),
+    /// chance of a spark branching, per second of flight
+    pub branching: f32,
+    /// range of launch temperatures [K]
+    pub temperature: (f32, f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    pub phase: Phase,
+    /// [s]
+    pub duration: f32,
+    pub params: PhaseParams,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// `phase` began at `time` [s] into the timeline
+    PhaseStarted { phase: Phase, time: f32 },
+    /// the last phase ended at `time` [s]
+    Finished { time: f32 },
+}
+
+/// Sequence of burn phases played back over simulated time.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    stages: Vec<Stage>,
+    elapsed: f32,
+    // index of the current stage, stages.len() once finished; None before
+    // the first advance
+    current: Option<usize>,
+}
+
+impl Timeline {
+    pub fn new(stages: Vec<Stage>) -> Self {
+        assert!(
+            !stages.is_empty(),
+            "it requires; stages.len() > 0\n\
+            stages must not be empty"
+        );
+        for stage in &stages {
+            assert!(
+                stage.duration > 0.0,
+                "it requires; duration > 0\n\
+                duration of {} must be grater than 0,\n\
+                but got {}",
+                stage.phase,
+                stage.duration
+            );
+        }
+        Self {
+            stages,
+            elapsed: 0.0,
+            current: None,
+        }
+    }
+
+    /// The classic one minute burn.
+    pub fn senko_hanabi() -> Self {
+        let stage = |phase, duration, rate, speed, branching, temperature| Stage {
+            phase,
+            duration,
+            params: PhaseParams {
+                rate,
+                speed,
+                branching,
+                temperature,
+            },
+        };
+        Self::new(vec![
+            stage(Phase::Tsubomi, 5.0, 5.0, (0.0, 2.0), 0.0, (1000.0, 1200.0)),
+            stage(
+                Phase::Botan,
+                10.0,
+                150.0,
+                (5.0, 20.0),
+                2.0,
+                (1300.0, 1500.0),
+            ),
+            stage(
+                Phase::Matsuba,
+                20.0,
+                300.0,
+                (10.0, 30.0),
+                8.0,
+                (1400.0, 1600.0),
+            ),
+            stage(
+                Phase::Yanagi,
+                15.0,
+                100.0,
+                (2.0, 8.0),
+                0.5,
+                (1100.0, 1300.0),
+            ),
+            stage(
+                Phase::ChiriGiku,
+                10.0,
+                20.0,
+                (0.0, 3.0),
+                0.0,
+                (900.0, 1100.0),
+            ),
+        ])
+    }
+
+    /// Stretches every stage so that the whole timeline lasts `duration` [s].
+    pub fn scaled(mut self, duration: f32) -> Self {
+        assert!(
+            duration > 0.0,
+            "it requires; duration > 0\n\
+            duration must be grater than 0,\n\
+            but got {duration}"
+        );
+        let factor = duration / self.duration();
+        for stage in self.stages.iter_mut() {
+            stage.duration *= factor;
+        }
+        self
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// Total length [s].
+    pub fn duration(&self) -> f32 {
+        self.stages.iter().map(|s| s.duration).sum()
+    }
+
+    /// Simulated time since the start [s].
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current == Some(self.stages.len())
+    }
+
+    pub fn phase(&self) -> Option<Phase> {
+        self.stage().map(|s| s.phase)
+    }
+
+    pub fn params(&self) -> Option<&PhaseParams> {
+        self.stage().map(|s| &s.params)
+    }
+
+    /// Moves `dt` [s] forward and returns the transitions crossed, in order.
+    pub fn advance(&mut self, dt: f32) -> Vec<Event> {
+        self.elapsed += dt;
+
+        // start time of every stage, plus the end of the last one
+        let starts = self
+            .stages
+            .iter()
+            .scan(0.0, |t, s| {
+                let start = *t;
+                *t += s.duration;
+                Some(start)
+            })
+            .chain([self.duration()])
+            .collect::<Vec<_>>();
+        let target = starts[1..]
+            .iter()
+            .take_while(|&&end| end <= self.elapsed)
+            .count();
+
+        let first = self.current.map_or(0, |i| i + 1);
+        let events = starts
+            .iter()
+            .enumerate()
+            .take(target + 1)
+            .skip(first)
+            .map(|(i, &time)| match self.stages.get(i) {
+                Some(stage) => Event::PhaseStarted {
+                    phase: stage.phase,
+                    time,
+                },
+                None => Event::Finished { time },
+            })
+            .collect();
+        self.current = Some(target.max(self.current.unwrap_or(0)));
+        events
+    }
+
+    fn stage(&self) -> Option<&Stage> {
+        self.stages.get(self.current.unwrap_or(0))
+    }
+}
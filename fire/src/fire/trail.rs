@@ -0,0 +1,121 @@
+use crate::palette::Palette;
+
+/// Where a spark was, and how hot, at one step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailPoint {
+    pub position: [f32; 3],
+    /// [K]
+    pub temperature: f32,
+}
+
+impl TrailPoint {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: [0, 1, 2]
+                .map(|i| self.position[i] + (other.position[i] - self.position[i]) * t),
+            temperature: self.temperature + (other.temperature - self.temperature) * t,
+        }
+    }
+}
+
+/// Past states of a spark in a fixed size ring buffer, newest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trail {
+    points: Vec<TrailPoint>,
+    // slot the next point goes into
+    next: usize,
+}
+
+impl Trail {
+    /// A trail of `capacity` points, all at `point`.
+    pub fn new(point: TrailPoint, capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "it requires; capacity > 0\n\
+            capacity must be grater than 0,\n\
+            but got {capacity}"
+        );
+        Self {
+            points: vec![point; capacity],
+            next: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Records a point, overwriting the oldest.
+    pub fn push(&mut self, point: TrailPoint) {
+        self.points[self.next] = point;
+        self.next = (self.next + 1) % self.points.len();
+    }
+
+    /// The point `age` steps back; 0 is the newest. Points older than the
+    /// capacity are clamped to the oldest one.
+    pub fn get(&self, age: usize) -> TrailPoint {
+        let n = self.points.len();
+        let age = age.min(n - 1);
+        self.points[(self.next + n - 1 - age) % n]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = TrailPoint> + '_ {
+        (0..self.points.len()).map(|age| self.get(age))
+    }
+}
+
+/// How trails are drawn behind the sparks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailStyle {
+    /// segments per trail; 1 is a single dash per step
+    pub length: usize,
+    /// share of the brightness lost towards the end of a trail
+    pub fade: f32,
+    /// drop in the color temperature towards the end of a trail [K]
+    pub cooling: f32,
+}
+
+impl Default for TrailStyle {
+    fn default() -> Self {
+        Self {
+            length: 1,
+            fade: 0.0,
+            cooling: 0.0,
+        }
+    }
+}
+
+impl TrailStyle {
+    /// Long streaks that dim and redden, like yanagi.
+    pub fn streaks(length: usize) -> Self {
+        Self {
+            length,
+            fade: 1.0,
+            cooling: 300.0,
+        }
+    }
+
+    /// Point `k` segments back from the head along a trail, `alpha` of the
+    /// way through the current step.
+    pub fn point(&self, trail: &Trail, current: TrailPoint, k: usize, alpha: f32) -> TrailPoint {
+        match k {
+            0 => trail.get(0).lerp(&current, alpha),
+            _ => trail.get(k).lerp(&trail.get(k - 1), alpha),
+        }
+    }
+
+    /// Linear RGBA of a point `k` segments back from the head.
+    pub fn color(&self, temperature: f32, k: usize) -> [f32; 4] {
+        let s = k as f32 / self.length as f32;
+        let temperature = temperature - self.cooling * s;
+        let palette = Palette::black_body();
+        let brightness = palette.brightness(temperature) * (1.0 - self.fade * s);
+        let color = palette.color(temperature);
+        [
+            brightness * color[0],
+            brightness * color[1],
+            brightness * color[2],
+            1.0,
+        ]
+    }
+}
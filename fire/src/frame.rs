@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+
+use crate::haze::Haze;
+use crate::palette::Palette;
+
+/// Geometry of one simulated frame, independent of the graphics backend.
+///
+/// Each spark is drawn as a line segment from its previous position to its
+/// current one; `segments` holds pairs of vertex indices. Glowing bodies
+/// bigger than a spark, like the hinotama, are drawn as `discs` over them in
+/// every draw mode.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameData {
+    /// xyz per vertex
+    pub positions: Vec<f32>,
+    /// rgba per vertex
+    pub colors: Vec<f32>,
+    /// radius [m] of the spark at each vertex, for point sprites; vertices
+    /// past the end have radius 0 and draw no sprite
+    pub sizes: Vec<f32>,
+    /// vertex index pairs, one per line segment
+    pub segments: Indices,
+    pub discs: Vec<Disc>,
+}
+
+/// Floats per disc uploaded to draw it: center xyz, rgba and radius.
+pub const DISC_FLOATS: usize = 8;
+
+/// A filled, camera-facing disc, brightest at its center and fading out to
+/// its rim like a sprite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Disc {
+    /// [m]
+    pub center: [f32; 3],
+    /// linear RGBA at the center
+    pub color: [f32; 4],
+    /// [m]
+    pub radius: f32,
+}
+
+/// Camera-facing quads along the segments of a frame, expanded in the
+/// vertex shader.
+///
+/// Each segment becomes four vertices, its two ends on either side, which
+/// move `side` half widths away from the segment, perpendicular to both the
+/// segment and the line of sight.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ribbons {
+    /// xyz per vertex, on the segment
+    pub positions: Vec<f32>,
+    /// rgba per vertex
+    pub colors: Vec<f32>,
+    /// xyz per vertex; the direction of the segment
+    pub tangents: Vec<f32>,
+    /// -1 or 1 per vertex
+    pub sides: Vec<f32>,
+    /// vertex index triples, two per segment
+    pub triangles: Indices,
+}
+
+impl Ribbons {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+}
+
+/// 32 bit words per spark in `Instances`.
+pub const INSTANCE_FLOATS: usize = 7;
+
+/// Sparks as compact per-instance attributes, expanded into geometry and
+/// shaded on the GPU.
+///
+/// Each spark is its head, where it was one step before, its temperature and
+/// its radius; the color is looked up from the temperature in a black body
+/// table, instead of uploading RGBA for every vertex of every trail. The
+/// temperature and radius go as half floats, which keep them to 3 digits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Instances {
+    /// position xyz, previous position xyz, and temperature [K] and radius
+    /// [m] packed by `pack_halves`, per spark, interleaved
+    pub data: Vec<f32>,
+}
+
+impl Instances {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(INSTANCE_FLOATS * capacity),
+        }
+    }
+
+    pub fn push(&mut self, position: [f32; 3], previous: [f32; 3], temperature: f32, radius: f32) {
+        self.data.extend(position);
+        self.data.extend(previous);
+        self.data.push(pack_halves(temperature, radius));
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len() / INSTANCE_FLOATS
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The geometry the instanced shader expands them into: a segment from
+    /// the previous position to the head, which alone carries the radius.
+    pub fn frame(&self) -> FrameData {
+        let palette = Palette::black_body();
+        let mut frame = FrameData::default();
+        for (i, instance) in self.data.chunks_exact(INSTANCE_FLOATS).enumerate() {
+            let [temperature, radius] = unpack_halves(instance[6]);
+            let emission = palette.emission(temperature);
+            let color = [emission[0], emission[1], emission[2], 1.0];
+            frame.positions.extend(&instance[3..6]);
+            frame.positions.extend(&instance[0..3]);
+            frame.colors.extend(color);
+            frame.colors.extend(color);
+            frame.sizes.extend([0.0, radius]);
+            frame.segments.push(2 * i as u32);
+            frame.segments.push(2 * i as u32 + 1);
+        }
+        frame
+    }
+}
+
+/// Two half floats in the bits of one word, `a` first in memory, as a pair
+/// of `HALF_FLOAT` attributes reads them.
+pub fn pack_halves(a: f32, b: f32) -> f32 {
+    f32::from_bits(u32::from(half_bits(a)) | u32::from(half_bits(b)) << 16)
+}
+
+pub fn unpack_halves(word: f32) -> [f32; 2] {
+    let bits = word.to_bits();
+    [half_value(bits as u16), half_value((bits >> 16) as u16)]
+}
+
+// IEEE 754 binary16, rounded to nearest even
+fn half_bits(x: f32) -> u16 {
+    let bits = x.to_bits();
+    let sign = (bits >> 16) as u16 & 0x8000;
+    let exponent = (bits >> 23 & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let e = exponent - 127 + 15;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let (half, rest, midpoint) = if e > 0 {
+        ((e as u32) << 10 | mantissa >> 13, mantissa & 0x1fff, 0x1000)
+    } else if e >= -10 {
+        // subnormal; the implicit bit shifts into the mantissa
+        let m = mantissa | 0x80_0000;
+        let shift = (14 - e) as u32;
+        (m >> shift, m & ((1 << shift) - 1), 1 << (shift - 1))
+    } else {
+        return sign;
+    };
+    let round = rest > midpoint || (rest == midpoint && half & 1 == 1);
+    // a carry out of the mantissa steps the exponent, up to infinity
+    sign | (half + round as u32) as u16
+}
+
+fn half_value(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (bits >> 10 & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * libm::exp2f(-24.0),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * libm::exp2f((exponent - 15) as f32),
+    }
+}
+
+impl FrameData {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.segments.len() / 2
+    }
+
+    /// Appends the geometry of `other`, shifting its indices past ours.
+    pub fn extend(&mut self, other: &FrameData) {
+        let offset = self.vertex_count() as u32;
+        self.positions.extend(&other.positions);
+        self.colors.extend(&other.colors);
+        self.sizes.resize(offset as usize, 0.0);
+        self.sizes.extend(&other.sizes);
+        for i in other.segments.iter() {
+            self.segments.push(i + offset);
+        }
+        self.discs.extend(&other.discs);
+    }
+
+    /// Radius [m] of every vertex, 0 where `sizes` falls short.
+    pub fn padded_sizes(&self) -> Cow<'_, [f32]> {
+        let vertex_count = self.vertex_count();
+        if self.sizes.len() == vertex_count {
+            return Cow::Borrowed(&self.sizes);
+        }
+        let mut sizes = self.sizes.clone();
+        sizes.resize(vertex_count, 0.0);
+        Cow::Owned(sizes)
+    }
+
+    /// Dims and reddens every vertex and disc by its distance from `eye`.
+    pub fn attenuate(&mut self, haze: &Haze, eye: [f32; 3]) {
+        let transmittance = |p: &[f32]| {
+            let distance = (0..3).map(|i| (p[i] - eye[i]).powi(2)).sum::<f32>();
+            haze.transmittance(distance.sqrt())
+        };
+        let vertices = self.positions.chunks_exact(3);
+        for (position, color) in vertices.zip(self.colors.chunks_exact_mut(4)) {
+            let t = transmittance(position);
+            (0..3).for_each(|c| color[c] *= t[c]);
+        }
+        for disc in self.discs.iter_mut() {
+            let t = transmittance(&disc.center);
+            (0..3).for_each(|c| disc.color[c] *= t[c]);
+        }
+    }
+
+    /// Expands every segment into a quad of two triangles.
+    pub fn ribbons(&self) -> Ribbons {
+        let vertex_count = 4 * self.segment_count();
+        let mut ribbons = Ribbons {
+            positions: Vec::with_capacity(3 * vertex_count),
+            colors: Vec::with_capacity(4 * vertex_count),
+            tangents: Vec::with_capacity(3 * vertex_count),
+            sides: Vec::with_capacity(vertex_count),
+            triangles: Indices::with_capacity(6 * self.segment_count(), vertex_count),
+        };
+
+        let mut indices = self.segments.iter();
+        while let (Some(i), Some(j)) = (indices.next(), indices.next()) {
+            let (i, j) = (i as usize, j as usize);
+            let a = &self.positions[3 * i..3 * i + 3];
+            let b = &self.positions[3 * j..3 * j + 3];
+            let tangent = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+
+            let base = ribbons.vertex_count() as u32;
+            for k in [i, j] {
+                for side in [-1.0, 1.0] {
+                    ribbons.positions.extend(&self.positions[3 * k..3 * k + 3]);
+                    ribbons.colors.extend(&self.colors[4 * k..4 * k + 4]);
+                    ribbons.tangents.extend(tangent);
+                    ribbons.sides.push(side);
+                }
+            }
+            for k in [0, 1, 2, 2, 1, 3] {
+                ribbons.triangles.push(base + k);
+            }
+        }
+        ribbons
+    }
+}
+
+/// Vertex indices, 16 bit while every vertex fits and 32 bit beyond.
+///
+/// Half the index memory and bandwidth for ordinary frames, with no cap on
+/// the particle count for dense ones. WebGL2 always restarts primitives at
+/// the largest index of the type, so 0xFFFF is left out of the 16 bit range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Default for Indices {
+    fn default() -> Self {
+        Self::U16(vec![])
+    }
+}
+
+impl From<Vec<u16>> for Indices {
+    fn from(indices: Vec<u16>) -> Self {
+        Self::U16(indices)
+    }
+}
+
+impl From<Vec<u32>> for Indices {
+    fn from(indices: Vec<u32>) -> Self {
+        Self::U32(indices)
+    }
+}
+
+impl Indices {
+    /// Empty indices wide enough for `vertex_count` vertices.
+    pub fn with_capacity(capacity: usize, vertex_count: usize) -> Self {
+        if vertex_count <= u16::MAX as usize {
+            Self::U16(Vec::with_capacity(capacity))
+        } else {
+            Self::U32(Vec::with_capacity(capacity))
+        }
+    }
+
+    /// Appends an index, widening to 32 bit if it does not fit in 16, or is
+    /// the 16 bit restart index.
+    pub fn push(&mut self, index: u32) {
+        match self {
+            Self::U16(indices) => match u16::try_from(index) {
+                Ok(index) if index != u16::MAX => indices.push(index),
+                _ => {
+                    let mut wide = indices.iter().map(|&i| i as u32).collect::<Vec<_>>();
+                    wide.push(index);
+                    *self = Self::U32(wide);
+                }
+            },
+            Self::U32(indices) => indices.push(index),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::U16(indices) => indices.len(),
+            Self::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes per index.
+    pub fn stride(&self) -> usize {
+        match self {
+            Self::U16(_) => std::mem::size_of::<u16>(),
+            Self::U32(_) => std::mem::size_of::<u32>(),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = u32> + '_> {
+        match self {
+            Self::U16(indices) => Box::new(indices.iter().map(|&i| i as u32)),
+            Self::U32(indices) => Box::new(indices.iter().copied()),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use black_body::atmosphere::Atmosphere;
+use black_body::BlackBody;
+
+// the extinction is fitted on a spark this hot, seen this far away
+const REFERENCE_TEMPERATURE: f64 = 2000.0; // [K]
+const REFERENCE_DISTANCE: f64 = 1_000.0; // [m]
+
+/// Extinction of the air per RGB channel, for tinting sparks by their
+/// distance from the eye.
+///
+/// Attenuating the spectrum of every vertex before `to_rgb` is far too slow
+/// to run on every frame; over the temperatures of sparks the transmittance
+/// of the color hardly changes, so one coefficient per channel, fitted with
+/// `BlackBody::color_through`, stands in for it. It stays within a few
+/// percent of the spectral result out to a kilometer in 5 km visibility.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Haze {
+    /// [1/m]
+    pub extinction: [f32; 3],
+}
+
+impl Haze {
+    pub fn new(atmosphere: &Atmosphere) -> Self {
+        let body = BlackBody::new(REFERENCE_TEMPERATURE);
+        let near = body.color_for_eye();
+        let far = body.color_through(atmosphere, REFERENCE_DISTANCE);
+        let extinction = [0, 1, 2].map(|c| (-(far[c] / near[c]).ln() / REFERENCE_DISTANCE) as f32);
+        Self { extinction }
+    }
+
+    /// Fraction of each channel left after `distance` [m] of air.
+    pub fn transmittance(&self, distance: f32) -> [f32; 3] {
+        self.extinction.map(|k| libm::expf(-k * distance))
+    }
+}
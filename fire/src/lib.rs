@@ -1,48 +1,311 @@
 mod app;
-mod fire;
+pub mod camera;
+pub mod fire;
+pub mod frame;
+pub mod haze;
+pub mod palette;
+pub mod renderer;
+pub mod rng;
 mod shader;
+pub mod timestep;
 
 use app::App;
-use fire::Fire;
-use std::{cell::RefCell, rc::Rc};
+use black_body::atmosphere::Atmosphere;
+use black_body::cvd::Cvd;
+use camera::controls::{Button, Input};
+use fire::emitter::Emitter;
+use fire::hinotama::Hinotama;
+use fire::kernel::KernelSimulation;
+use fire::physics::Physics;
+use fire::thermal::Oxidation;
+use fire::timeline::{Event, Timeline};
+use fire::trail::TrailStyle;
+use fire::{Fire, FireConfig};
+use haze::Haze;
+use renderer::hdr::Hdr;
+use renderer::webgl::WebGlRenderer;
+use renderer::DrawMode;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 use wasm_bindgen::{prelude::*, JsCast};
 use web_sys::*;
 
-const WIDTH: u32 = 2560;
-const HEIGHT: u32 = 1600;
-
 #[wasm_bindgen(start)]
 pub fn start() -> Result<(), JsValue> {
-    let fire = Fire::new();
-    let mut app = App::new(WIDTH, HEIGHT, fire)?;
+    let mut renderer = WebGlRenderer::new()?;
+    let resized = observe_resize(renderer.canvas())?;
+    let inputs = listen_input(renderer.canvas())?;
+    renderer.set_cvd(cvd_from_query()?);
+    renderer.set_mode(mode_from_query()?);
+    // without float render targets, plain lines still work
+    if let Err(e) = renderer.set_hdr(hdr_from_query()?) {
+        console::warn_1(&e);
+    }
+    let seed = seed_from_query()?;
+    console::log_1(&format!("fire seed: {seed}").into());
+    let config = FireConfig {
+        seed,
+        physics: Physics::default().with_oxidation(Oxidation::default()),
+        hinotama: Some(Hinotama::default()),
+        trail: TrailStyle::streaks(16),
+        timeline: Some(Timeline::senko_hanabi()),
+        ..FireConfig::default()
+    };
+    let mut app = App::new(renderer, Fire::with_config(config));
+    app.clock.time_scale = time_scale_from_query()?;
+    // `?instanced=on` uploads compact sparks and shades them on the GPU
+    app.instanced = query_parameter("instanced")?.as_deref() == Some("on");
+    app.simulation = simulation_from_query(seed)?;
+    app.haze = haze_from_query()?;
 
     let f = Rc::new(RefCell::new(None));
     let g = f.clone();
 
-    let mut i = 0;
-    *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
-        if i > 1000 {
-            // Drop our handle to this closure so that it will get cleaned
-            // up once we return.
-            let _ = f.borrow_mut().take();
-            return;
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move |timestamp: f64| {
+        // zooming or moving to another screen changes the ratio without
+        // resizing the element
+        let ratio = web_sys::window().map_or(1.0, |w| w.device_pixel_ratio());
+        if resized.take() || ratio != app.renderer.device_pixel_ratio() {
+            if let Err(e) = app.renderer.fit_to_canvas() {
+                console::warn_1(&e);
+            }
+        }
+        for input in inputs.borrow_mut().drain(..) {
+            app.input(input);
         }
-
-        // Set the body's text content to how many times this
-        // requestAnimationFrame callback has fired.
-        i += 1;
-
         // Schedule ourself for another requestAnimationFrame callback.
-        let _ = app.render();
+        let _ = app.render(timestamp);
+        for event in app.model.drain_events() {
+            log_event(event);
+        }
         request_animation_frame(f.borrow().as_ref().unwrap());
-    }) as Box<dyn FnMut()>));
+    }) as Box<dyn FnMut(f64)>));
 
     request_animation_frame(g.borrow().as_ref().unwrap());
 
     Ok(())
 }
 
-fn request_animation_frame(closure: &Closure<dyn FnMut()>) {
+// Flags resizes of `canvas`, to be applied once before the next frame
+// however many come in between.
+fn observe_resize(canvas: &HtmlCanvasElement) -> Result<Rc<Cell<bool>>, JsValue> {
+    let resized = Rc::new(Cell::new(false));
+    let flag = resized.clone();
+    let callback = Closure::wrap(Box::new(move || flag.set(true)) as Box<dyn FnMut()>);
+    let observer = ResizeObserver::new(callback.as_ref().unchecked_ref())?;
+    observer.observe(canvas);
+    // observes for the lifetime of the page
+    callback.forget();
+    Ok(resized)
+}
+
+// Queues pointer and wheel events on `canvas` as camera input for the next
+// frame; dragging orbits, dragging with the right button or shift pans, and
+// the wheel or a pinch zooms.
+fn listen_input(canvas: &HtmlCanvasElement) -> Result<Rc<RefCell<Vec<Input>>>, JsValue> {
+    let inputs = Rc::new(RefCell::new(vec![]));
+    // in heights of the canvas, so that drags feel the same at any size
+    let position = {
+        let canvas = canvas.clone();
+        move |e: &PointerEvent| {
+            let height = canvas.client_height().max(1) as f32;
+            [e.offset_x() as f32 / height, e.offset_y() as f32 / height]
+        }
+    };
+
+    let (queue, target, at) = (inputs.clone(), canvas.clone(), position.clone());
+    listen(canvas, "pointerdown", move |e: PointerEvent| {
+        // keep the drag when the pointer leaves the canvas
+        let _ = target.set_pointer_capture(e.pointer_id());
+        let button = if e.button() == 0 && !e.shift_key() {
+            Button::Primary
+        } else {
+            Button::Secondary
+        };
+        queue.borrow_mut().push(Input::Down {
+            id: e.pointer_id(),
+            position: at(&e),
+            button,
+            time: e.time_stamp(),
+        });
+    })?;
+    let queue = inputs.clone();
+    listen(canvas, "pointermove", move |e: PointerEvent| {
+        queue.borrow_mut().push(Input::Move {
+            id: e.pointer_id(),
+            position: position(&e),
+            time: e.time_stamp(),
+        });
+    })?;
+    for kind in ["pointerup", "pointercancel"] {
+        let queue = inputs.clone();
+        listen(canvas, kind, move |e: PointerEvent| {
+            queue.borrow_mut().push(Input::Up {
+                id: e.pointer_id(),
+                time: e.time_stamp(),
+            });
+        })?;
+    }
+    let queue = inputs.clone();
+    listen(canvas, "wheel", move |e: WheelEvent| {
+        e.prevent_default();
+        // pixels, lines or pages
+        let notches = match e.delta_mode() {
+            WheelEvent::DOM_DELTA_PIXEL => e.delta_y() / 100.0,
+            WheelEvent::DOM_DELTA_LINE => e.delta_y() / 3.0,
+            _ => e.delta_y(),
+        };
+        queue.borrow_mut().push(Input::Wheel {
+            notches: notches as f32,
+        });
+    })?;
+    // the right button pans instead
+    listen(canvas, "contextmenu", |e: web_sys::Event| {
+        e.prevent_default()
+    })?;
+    Ok(inputs)
+}
+
+fn listen<E: wasm_bindgen::convert::FromWasmAbi + 'static>(
+    target: &EventTarget,
+    kind: &str,
+    handler: impl FnMut(E) + 'static,
+) -> Result<(), JsValue> {
+    let callback = Closure::wrap(Box::new(handler) as Box<dyn FnMut(E)>);
+    target.add_event_listener_with_callback(kind, callback.as_ref().unchecked_ref())?;
+    // listens for the lifetime of the page
+    callback.forget();
+    Ok(())
+}
+
+// `?cvd=protanopia|deuteranopia|tritanopia` simulates a color vision deficiency
+fn cvd_from_query() -> Result<Option<Cvd>, JsValue> {
+    let deficiency = query_parameter("cvd")?
+        .map(|name| name.parse().map_err(|e: String| JsValue::from_str(&e)))
+        .transpose()?;
+    Ok(deficiency.map(Cvd::new))
+}
+
+// `?mode=lines|sprites|ribbons` picks how sparks are drawn, and `?width=<m>`
+// the ribbon width
+fn mode_from_query() -> Result<DrawMode, JsValue> {
+    let mode = query_parameter("mode")?
+        .map(|name| name.parse().map_err(|e: String| JsValue::from_str(&e)))
+        .transpose()?
+        .unwrap_or_default();
+    match (mode, query_parameter("width")?) {
+        (DrawMode::Ribbons { .. }, Some(width)) => {
+            let width = width
+                .parse()
+                .map_err(|e| JsValue::from_str(&format!("invalid width {width}: {e}")))?;
+            Ok(DrawMode::Ribbons { width })
+        }
+        _ => Ok(mode),
+    }
+}
+
+// `?tonemap=reinhard|aces|agx&exposure=<f32>` tune the HDR output, and
+// `?hdr=off` draws plain opaque lines instead
+fn hdr_from_query() -> Result<Option<Hdr>, JsValue> {
+    if query_parameter("hdr")?.as_deref() == Some("off") {
+        return Ok(None);
+    }
+    let mut hdr = Hdr::default();
+    if let Some(name) = query_parameter("tonemap")? {
+        let tone_mapping = name.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        hdr = hdr.with_tone_mapping(tone_mapping);
+    }
+    if let Some(exposure) = query_parameter("exposure")? {
+        let exposure = exposure
+            .parse()
+            .map_err(|e| JsValue::from_str(&format!("invalid exposure {exposure}: {e}")))?;
+        hdr = hdr.with_exposure(exposure);
+    }
+    Ok(Some(hdr))
+}
+
+// `?simulation=gpu&particles=<count>` runs a steady fountain of sparks in
+// transform feedback in place of the sparkler
+fn simulation_from_query(seed: u64) -> Result<Option<KernelSimulation>, JsValue> {
+    if query_parameter("simulation")?.as_deref() != Some("gpu") {
+        return Ok(None);
+    }
+    let particles: usize = match query_parameter("particles")? {
+        Some(count) => count
+            .parse()
+            .map_err(|e| JsValue::from_str(&format!("invalid particles {count}: {e}")))?,
+        None => 100_000,
+    };
+    let emitter = Emitter::default();
+    // as many sparks a second as the pool holds over the longest lifetime
+    let rate = particles as f32 / emitter.lifetime.1;
+    let config = FireConfig {
+        seed,
+        emitter: emitter.with_rate(rate),
+        max_particles: particles,
+        ..FireConfig::default()
+    };
+    Ok(Some(KernelSimulation::new(&config)))
+}
+
+// `?seed=<u64>` replays a run; otherwise every page load gets a new seed
+fn seed_from_query() -> Result<u64, JsValue> {
+    match query_parameter("seed")? {
+        Some(seed) => seed
+            .parse()
+            .map_err(|e| JsValue::from_str(&format!("invalid seed {seed}: {e}"))),
+        None => Ok(js_sys::Date::now() as u64),
+    }
+}
+
+// `?timescale=0.25` plays in slow motion
+fn time_scale_from_query() -> Result<f32, JsValue> {
+    match query_parameter("timescale")? {
+        Some(scale) => scale
+            .parse()
+            .map_err(|e| JsValue::from_str(&format!("invalid time scale {scale}: {e}"))),
+        None => Ok(1.0),
+    }
+}
+
+// `?visibility=5000` sees the fire through hazy air, 5 km visibility
+fn haze_from_query() -> Result<Option<Haze>, JsValue> {
+    match query_parameter("visibility")? {
+        Some(visibility) => {
+            let visibility = visibility
+                .parse()
+                .map_err(|e| JsValue::from_str(&format!("invalid visibility {visibility}: {e}")))?;
+            Ok(Some(Haze::new(
+                &Atmosphere::new().with_visibility(visibility),
+            )))
+        }
+        None => Ok(None),
+    }
+}
+
+fn log_event(event: Event) {
+    let message = match event {
+        Event::PhaseStarted { phase, time } => format!("{phase} at {time:.1} s"),
+        Event::Finished { time } => format!("burnt out at {time:.1} s"),
+    };
+    console::log_1(&message.into());
+}
+
+fn query_parameter(name: &str) -> Result<Option<String>, JsValue> {
+    let window = web_sys::window().expect("should have a window in this context");
+    let search = window.location().search()?;
+    let value = search
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string());
+    Ok(value)
+}
+
+fn request_animation_frame(closure: &Closure<dyn FnMut(f64)>) {
     let window = web_sys::window().expect("should have a window in this context");
     window
         .request_animation_frame(closure.as_ref().unchecked_ref())
@@ -0,0 +1,134 @@
+use black_body::spectrum::ColorFunction;
+use black_body::BlackBody;
+use std::sync::OnceLock;
+
+// below this the visible radiance underflows, and the color with it
+const MIN_TEMPERATURE: f32 = 500.0; // [K]
+const MAX_TEMPERATURE: f32 = 5000.0; // [K]
+const TEMPERATURE_STEP: f32 = 10.0; // [K]
+
+// brightness is 1 at this temperature
+const REFERENCE_TEMPERATURE: f64 = 1500.0; // [K]
+
+/// Black body colors tabulated by temperature.
+///
+/// `BlackBody::color_for_eye` integrates the whole visible spectrum, which is
+/// far too slow to run for every spark on every step, so the simulation
+/// looks colors up here instead.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: Vec<[f32; 3]>,
+    brightness: Vec<f32>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        let steps = ((MAX_TEMPERATURE - MIN_TEMPERATURE) / TEMPERATURE_STEP) as usize;
+        let temperatures = (0..=steps).map(|i| MIN_TEMPERATURE + i as f32 * TEMPERATURE_STEP);
+        let colors = temperatures
+            .clone()
+            // dim reds fall slightly outside the gamut, below zero blue
+            .map(|t| {
+                BlackBody::new(t as f64)
+                    .color_for_eye()
+                    .map(|c| c.max(0.0) as f32)
+            })
+            .collect();
+
+        let reference = Self::luminance(REFERENCE_TEMPERATURE);
+        let brightness = temperatures
+            .map(|t| Self::lightness(Self::luminance(t as f64) / reference) as f32)
+            .collect();
+        Self { colors, brightness }
+    }
+
+    // luminance Y, up to a constant factor
+    fn luminance(temperature: f64) -> f64 {
+        let body = BlackBody::new(temperature);
+        (380..=780)
+            .map(|l| body.radiance(l as f64 * 1.0e-9) * ColorFunction::y(l as f64))
+            .sum()
+    }
+
+    // CIE 1976 L*, scaled to 1 at white
+    // ref: https://en.wikipedia.org/wiki/CIELAB_color_space
+    fn lightness(y: f64) -> f64 {
+        let epsilon = (6.0_f64 / 29.0).powi(3);
+        let f = if y > epsilon {
+            y.cbrt()
+        } else {
+            y / (3.0 * (6.0_f64 / 29.0).powi(2)) + 4.0 / 29.0
+        };
+        (116.0 * f - 16.0) / 100.0
+    }
+
+    /// Shared table, built on first use.
+    pub fn black_body() -> &'static Self {
+        static PALETTE: OnceLock<Palette> = OnceLock::new();
+        PALETTE.get_or_init(Self::new)
+    }
+
+    /// Color at the temperature [K], normalized to a mean of 1, linearly
+    /// interpolated and clamped to `range`.
+    pub fn color(&self, temperature: f32) -> [f32; 3] {
+        let (i, t) = self.locate(temperature);
+        let (a, b) = (self.colors[i], self.colors[i + 1]);
+        [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t)
+    }
+
+    /// Perceived brightness (CIE L*) of a black body at the temperature [K],
+    /// relative to one at 1500 K.
+    ///
+    /// Visible radiance falls by orders of magnitude over a few hundred
+    /// kelvin; lightness rather than luminance lets a cooling spark fade out
+    /// gradually towards the Draper point.
+    pub fn brightness(&self, temperature: f32) -> f32 {
+        let (i, t) = self.locate(temperature);
+        let (a, b) = (self.brightness[i], self.brightness[i + 1]);
+        a + (b - a) * t
+    }
+
+    // index of the entry below the temperature, and the fraction towards the
+    // next one
+    fn locate(&self, temperature: f32) -> (usize, f32) {
+        let last = self.colors.len() - 1;
+        let x = ((temperature - MIN_TEMPERATURE) / TEMPERATURE_STEP).clamp(0.0, last as f32);
+        let i = (x as usize).min(last - 1);
+        (i, x - i as f32)
+    }
+
+    /// Light emitted at every entry, the color scaled by the brightness; the
+    /// rows of the texture the instanced shader looks temperatures up in.
+    pub fn emission_table(&self) -> Vec<[f32; 3]> {
+        self.colors
+            .iter()
+            .zip(&self.brightness)
+            .map(|(color, &brightness)| color.map(|c| c * brightness))
+            .collect()
+    }
+
+    /// Light emitted at the temperature [K], interpolated from
+    /// `emission_table` as a linearly filtered texture would.
+    pub fn emission(&self, temperature: f32) -> [f32; 3] {
+        let (i, t) = self.locate(temperature);
+        let a = self.colors[i].map(|c| c * self.brightness[i]);
+        let b = self.colors[i + 1].map(|c| c * self.brightness[i + 1]);
+        [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t)
+    }
+
+    /// Temperatures [K] of the first and last entries.
+    pub fn range(&self) -> (f32, f32) {
+        (MIN_TEMPERATURE, MAX_TEMPERATURE)
+    }
+
+    /// The table as RGB rows from the lowest temperature up.
+    pub fn colors(&self) -> &[[f32; 3]] {
+        &self.colors
+    }
+}
@@ -0,0 +1,258 @@
+pub mod hdr;
+pub mod software;
+pub mod webgl;
+
+extern crate nalgebra_glm as glm;
+
+use black_body::cvd::{Cvd, Method};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use super::camera::{Camera, FOVY};
+use super::fire::kernel::KernelSimulation;
+use super::frame::{Disc, FrameData, Indices, Instances};
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// How the segments of a frame are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DrawMode {
+    /// `GL_LINES`, one pixel wide whatever the resolution
+    #[default]
+    Lines,
+    /// a round, soft-edged sprite at every vertex, `scale` times the spark
+    /// radius, for the glow around it
+    Sprites { scale: f32 },
+    /// camera-facing quads along the segments, `width` [m] wide
+    Ribbons { width: f32 },
+}
+
+impl DrawMode {
+    /// Sprites glowing 8 times as wide as the sparks.
+    pub fn sprites() -> Self {
+        Self::Sprites { scale: 8.0 }
+    }
+
+    /// Ribbons 4 mm wide.
+    pub fn ribbons() -> Self {
+        Self::Ribbons { width: 4.0e-3 }
+    }
+}
+
+impl FromStr for DrawMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lines" => Ok(Self::Lines),
+            "sprites" => Ok(Self::sprites()),
+            "ribbons" => Ok(Self::ribbons()),
+            _ => Err(format!("expected lines, sprites or ribbons, but got {s}")),
+        }
+    }
+}
+
+/// A backend that draws simulated frames.
+pub trait Renderer {
+    type Error;
+
+    /// Views the following frames through `camera`.
+    fn set_camera(&mut self, camera: &Camera);
+
+    fn render(&mut self, frame: &FrameData) -> Result<(), Self::Error>;
+
+    /// Draws the sparks as instances, with `frame` over them for anything
+    /// else; backends without instancing expand them on the CPU.
+    fn render_instanced(
+        &mut self,
+        instances: &Instances,
+        frame: &FrameData,
+    ) -> Result<(), Self::Error> {
+        let mut expanded = instances.frame();
+        expanded.extend(frame);
+        self.render(&expanded)
+    }
+
+    /// Advances `simulation` one step; backends with transform feedback run
+    /// it on the GPU instead, where its particles then live.
+    fn simulate(&mut self, simulation: &mut KernelSimulation) -> Result<(), Self::Error> {
+        simulation.step();
+        Ok(())
+    }
+
+    /// Draws the live sparks of `simulation`, with `frame` over them.
+    fn render_simulation(
+        &mut self,
+        simulation: &KernelSimulation,
+        frame: &FrameData,
+    ) -> Result<(), Self::Error> {
+        self.render_instanced(&simulation.instances(), frame)
+    }
+}
+
+/// Bytes uploaded to the GPU, in the last frame and since the start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ByteCounter {
+    pub frame: usize,
+    pub total: u64,
+}
+
+impl ByteCounter {
+    pub fn begin_frame(&mut self) {
+        self.frame = 0;
+    }
+
+    pub fn add(&mut self, bytes: usize) {
+        self.frame += bytes;
+        self.total += bytes as u64;
+    }
+}
+
+/// Pixels spanned by a meter at unit distance from the eye, on a viewport
+/// `height` pixels tall.
+pub fn pixels_per_meter(height: u32) -> f32 {
+    0.5 * height as f32 / libm::tanf(0.5 * FOVY)
+}
+
+/// Diameter [px] of the sprite of a spark of `radius` [m], at clip space
+/// `w` (the distance along the line of sight), and the factor dimming its
+/// color; below a pixel a sprite dims instead of shrinking, so that it keeps
+/// its light.
+pub fn sprite_diameter(radius: f32, scale: f32, w: f32, height: u32) -> (f32, f32) {
+    let diameter = 2.0 * radius * scale * pixels_per_meter(height) / w;
+    let clamped = diameter.max(1.0);
+    (clamped, (diameter * diameter) / (clamped * clamped))
+}
+
+/// Intensity across a sprite at `(x, y)` in [-1, 1] from its center;
+/// `None` outside the disc.
+pub fn sprite_falloff(x: f32, y: f32) -> Option<f32> {
+    let r2 = x * x + y * y;
+    (r2 <= 1.0).then_some((1.0 - r2) * (1.0 - r2))
+}
+
+/// Offset of a ribbon vertex at `position` from the segment, perpendicular to
+/// its `tangent` and to the line of sight from `eye`; 0 if they are parallel.
+pub fn ribbon_offset(
+    position: [f32; 3],
+    tangent: [f32; 3],
+    side: f32,
+    width: f32,
+    eye: [f32; 3],
+) -> [f32; 3] {
+    let view = glm::Vec3::from(eye) - glm::Vec3::from(position);
+    let normal = glm::Vec3::from(tangent).cross(&view);
+    let length = normal.norm();
+    if length < 1.0e-12 {
+        return [0.0; 3];
+    }
+    (normal * (0.5 * side * width / length)).into()
+}
+
+/// Color vision deficiency post-processing as a linear RGB matrix.
+pub fn cvd_matrix(cvd: Option<Cvd>) -> [[f32; 3]; 3] {
+    // Brettel's method is not linear, so it falls back to Machado's matrix
+    let matrix = cvd
+        .map(|cvd| {
+            cvd.matrix().unwrap_or_else(|| {
+                let machado = Method::Machado { severity: 1.0 };
+                cvd.with_method(machado).matrix().unwrap()
+            })
+        })
+        .map(|m| m.map(|row| row.map(|m| m as f32)));
+    matrix.unwrap_or(IDENTITY)
+}
+
+/// Vertex attributes and indices uploaded to draw a frame in a mode: lines
+/// read no sizes, and sprites, drawn as points, no indices.
+pub struct FrameUpload<'a> {
+    pub vertex_count: usize,
+    /// one slice per attribute, in the order of the shader locations
+    pub data: Vec<Cow<'a, [f32]>>,
+    pub indices: Cow<'a, Indices>,
+}
+
+impl<'a> FrameUpload<'a> {
+    pub fn new(frame: &'a FrameData, mode: DrawMode) -> Self {
+        let positions = Cow::Borrowed(&frame.positions[..]);
+        let colors = Cow::Borrowed(&frame.colors[..]);
+        match mode {
+            DrawMode::Lines => Self {
+                vertex_count: frame.vertex_count(),
+                data: vec![positions, colors],
+                indices: Cow::Borrowed(&frame.segments),
+            },
+            DrawMode::Sprites { .. } => Self {
+                vertex_count: frame.vertex_count(),
+                data: vec![positions, colors, frame.padded_sizes()],
+                indices: Cow::Owned(Indices::default()),
+            },
+            DrawMode::Ribbons { .. } => {
+                let ribbons = frame.ribbons();
+                Self {
+                    vertex_count: ribbons.vertex_count(),
+                    data: vec![
+                        Cow::Owned(ribbons.positions),
+                        Cow::Owned(ribbons.colors),
+                        Cow::Owned(ribbons.tangents),
+                        Cow::Owned(ribbons.sides),
+                    ],
+                    indices: Cow::Owned(ribbons.triangles),
+                }
+            }
+        }
+    }
+
+    pub fn slices(&self) -> Vec<&[f32]> {
+        self.data.iter().map(|d| &d[..]).collect()
+    }
+
+    pub fn bytes(&self) -> usize {
+        upload_bytes(&self.slices(), &self.indices)
+    }
+}
+
+/// Centers, colors and radii of the discs, as uploaded to draw them.
+pub fn disc_attributes(discs: &[Disc]) -> [Vec<f32>; 3] {
+    [
+        discs.iter().flat_map(|d| d.center).collect(),
+        discs.iter().flat_map(|d| d.color).collect(),
+        discs.iter().map(|d| d.radius).collect(),
+    ]
+}
+
+/// Bytes written by uploading `data` and `indices`; what every backend adds
+/// to its `ByteCounter`.
+pub fn upload_bytes(data: &[&[f32]], indices: &Indices) -> usize {
+    let floats = data.iter().map(|d| d.len()).sum::<usize>();
+    floats * std::mem::size_of::<f32>() + indices.len() * indices.stride()
+}
+
+/// Capacity to reallocate a GPU buffer to, so that it holds `required`
+/// elements. Grows geometrically, so a rising particle count costs only a
+/// logarithmic number of reallocations; never shrinks.
+pub fn grow_capacity(capacity: usize, required: usize) -> usize {
+    if required <= capacity {
+        return capacity;
+    }
+    required.max(2 * capacity).next_power_of_two()
+}
+
+/// Drawing buffer size [px] of a canvas laid out `client_width` by
+/// `client_height` CSS pixels on a screen of `device_pixel_ratio`, scaled
+/// down to at most `max_size` a side keeping its aspect ratio; never empty,
+/// so that the projection stays finite.
+pub fn drawing_buffer_size(
+    client_width: f64,
+    client_height: f64,
+    device_pixel_ratio: f64,
+    max_size: u32,
+) -> (u32, u32) {
+    let (width, height) = (
+        client_width * device_pixel_ratio,
+        client_height * device_pixel_ratio,
+    );
+    let scale = (max_size as f64 / width.max(height)).min(1.0);
+    let size = |x: f64| ((x * scale).round() as u32).clamp(1, max_size);
+    (size(width), size(height))
+}
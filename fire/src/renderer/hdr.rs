@@ -0,0 +1,364 @@
+//! HDR post-processing: bloom and tone mapping.
+//!
+//! With HDR on, sparks add their light into a float buffer instead of
+//! occluding each other, so dense clusters and the hinotama exceed 1. The
+//! bright part of that buffer is blurred into a glow (dual-Kawase bloom), and
+//! the sum is tone mapped back to the display range.
+//!
+//! The functions here are the CPU reference of the WebGL passes in
+//! `shader/downsample.glsl`, `shader/upsample.glsl` and
+//! `shader/composite.glsl`; both sample with bilinear filtering and clamp to
+//! edge, so they agree up to float precision.
+use std::fmt;
+use std::str::FromStr;
+
+/// Curve mapping scene radiance in [0, ∞) to display values in [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapping {
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve
+    Aces,
+    /// Troy Sobotka's AgX, as fitted by Benjamin Wrensch
+    Agx,
+}
+
+impl ToneMapping {
+    /// Maps linear radiance, already scaled by the exposure, to linear
+    /// display RGB.
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        match self {
+            // ref: https://www.cs.utah.edu/docs/techreports/2002/pdf/UUCS-02-001.pdf
+            Self::Reinhard => rgb.map(|x| x / (1.0 + x)),
+            // ref: https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
+            Self::Aces => rgb.map(|x| {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                (x * (a * x + b) / (x * (c * x + d) + e)).clamp(0.0, 1.0)
+            }),
+            Self::Agx => agx(rgb),
+        }
+    }
+
+    /// Index of the operator in `composite.glsl`.
+    pub fn index(&self) -> i32 {
+        match self {
+            Self::Reinhard => 0,
+            Self::Aces => 1,
+            Self::Agx => 2,
+        }
+    }
+}
+
+impl fmt::Display for ToneMapping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Reinhard => "reinhard",
+            Self::Aces => "aces",
+            Self::Agx => "agx",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ToneMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "reinhard" => Ok(Self::Reinhard),
+            "aces" => Ok(Self::Aces),
+            "agx" => Ok(Self::Agx),
+            _ => Err(format!("expected reinhard, aces or agx, but got {s}")),
+        }
+    }
+}
+
+// ref: https://iolite-engine.com/blog_posts/minimal_agx_implementation
+// matrices are written as the columns of the GLSL mat3 constructors
+const AGX_INSET: [[f32; 3]; 3] = [
+    [0.842_479_06, 0.042_328_24, 0.042_375_655],
+    [0.078_433_6, 0.878_468_6, 0.078_433_6],
+    [0.079_223_745, 0.079_166_13, 0.879_143],
+];
+const AGX_OUTSET: [[f32; 3]; 3] = [
+    [1.196_879, -0.052_896_85, -0.052_971_635],
+    [-0.098_020_88, 1.151_903_1, -0.098_043_45],
+    [-0.099_029_74, -0.098_961_18, 1.151_073_7],
+];
+const AGX_MIN_EV: f32 = -12.473_93;
+const AGX_MAX_EV: f32 = 4.026_069;
+
+fn agx(rgb: [f32; 3]) -> [f32; 3] {
+    let columns = |m: &[[f32; 3]; 3], v: [f32; 3]| {
+        [0, 1, 2].map(|i| m[0][i] * v[0] + m[1][i] * v[1] + m[2][i] * v[2])
+    };
+    let encoded = columns(&AGX_INSET, rgb).map(|x| {
+        let ev = libm::log2f(x).clamp(AGX_MIN_EV, AGX_MAX_EV);
+        let x = (ev - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+        // default contrast sigmoid
+        let (x2, x4) = (x * x, x * x * x * x);
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    });
+    columns(&AGX_OUTSET, encoded).map(|x| libm::powf(x.max(0.0), 2.2))
+}
+
+/// Linear to sRGB transfer function.
+// ref: https://en.wikipedia.org/wiki/SRGB
+pub fn encode_srgb(linear: f32) -> f32 {
+    let x = linear.clamp(0.0, 1.0);
+    if x <= 0.003_130_8 {
+        12.92 * x
+    } else {
+        1.055 * libm::powf(x, 1.0 / 2.4) - 0.055
+    }
+}
+
+/// Glow around the radiance above `threshold`.
+///
+/// The buffer is halved `levels` times with the dual-Kawase downsampling
+/// filter, and then upsampled back, each level adding onto the one above.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bloom {
+    /// radiance below this does not glow
+    pub threshold: f32,
+    /// weight of the glow added to the scene; 0 disables the bloom
+    pub intensity: f32,
+    /// number of halvings; each doubles the reach of the glow
+    pub levels: usize,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            intensity: 0.6,
+            levels: 5,
+        }
+    }
+}
+
+impl Bloom {
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.intensity > 0.0 && self.levels > 0
+    }
+
+    /// Size of the buffer of the level, the first being half the screen.
+    pub fn level_size(width: u32, height: u32, level: usize) -> (u32, u32) {
+        (
+            (width >> (level + 1)).max(1),
+            (height >> (level + 1)).max(1),
+        )
+    }
+
+    /// The glow of the row-major radiance, at the same resolution and
+    /// before weighting by the intensity.
+    pub fn apply(&self, radiance: &[[f32; 3]], width: u32, height: u32) -> Vec<[f32; 3]> {
+        let size = (width * height) as usize;
+        if !self.is_enabled() {
+            return vec![[0.0; 3]; size];
+        }
+
+        let mut levels: Vec<Image> = Vec::with_capacity(self.levels);
+        let screen = Image {
+            width,
+            height,
+            pixels: radiance.to_vec(),
+        };
+        for level in 0..self.levels {
+            let source = levels.last().unwrap_or(&screen);
+            // only the first pass cuts off the dim part
+            let threshold = if level == 0 { self.threshold } else { 0.0 };
+            let (w, h) = Self::level_size(width, height, level);
+            levels.push(downsample(source, w, h, threshold));
+        }
+        for level in (1..self.levels).rev() {
+            let up = upsample(
+                &levels[level],
+                levels[level - 1].width,
+                levels[level - 1].height,
+            );
+            for (pixel, add) in levels[level - 1].pixels.iter_mut().zip(up.pixels) {
+                *pixel = [0, 1, 2].map(|c| pixel[c] + add[c]);
+            }
+        }
+
+        // the composite pass stretches the first level over the screen
+        let bloom = &levels[0];
+        (0..size as u32)
+            .map(|i| {
+                let u = ((i % width) as f32 + 0.5) / width as f32;
+                let v = ((i / width) as f32 + 0.5) / height as f32;
+                bloom.sample(u, v)
+            })
+            .collect()
+    }
+}
+
+/// Settings of the HDR path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hdr {
+    /// scale of the radiance before tone mapping
+    pub exposure: f32,
+    pub tone_mapping: ToneMapping,
+    pub bloom: Bloom,
+}
+
+impl Default for Hdr {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            tone_mapping: ToneMapping::Aces,
+            bloom: Bloom::default(),
+        }
+    }
+}
+
+impl Hdr {
+    pub fn with_exposure(mut self, exposure: f32) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn with_bloom(mut self, bloom: Bloom) -> Self {
+        self.bloom = bloom;
+        self
+    }
+
+    /// Display color of a pixel from its radiance and glow: exposure, tone
+    /// mapping, the color vision deficiency matrix, and sRGB encoding.
+    pub fn composite(&self, radiance: [f32; 3], glow: [f32; 3], cvd: &[[f32; 3]; 3]) -> [f32; 3] {
+        let hdr = [0, 1, 2].map(|c| (radiance[c] + self.bloom.intensity * glow[c]) * self.exposure);
+        let ldr = self.tone_mapping.apply(hdr);
+        cvd.map(|row| encode_srgb(row[0] * ldr[0] + row[1] * ldr[1] + row[2] * ldr[2]))
+    }
+
+    /// Display colors of the row-major radiance buffer.
+    pub fn resolve(
+        &self,
+        radiance: &[[f32; 3]],
+        width: u32,
+        height: u32,
+        cvd: &[[f32; 3]; 3],
+    ) -> Vec<[f32; 3]> {
+        let glow = self.bloom.apply(radiance, width, height);
+        radiance
+            .iter()
+            .zip(glow)
+            .map(|(&r, g)| self.composite(r, g, cvd))
+            .collect()
+    }
+}
+
+/// Row-major RGB buffer sampled like a `LINEAR`, `CLAMP_TO_EDGE` texture.
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 3]>,
+}
+
+impl Image {
+    fn texel(&self, x: i64, y: i64) -> [f32; 3] {
+        let x = x.clamp(0, self.width as i64 - 1);
+        let y = y.clamp(0, self.height as i64 - 1);
+        self.pixels[(y * self.width as i64 + x) as usize]
+    }
+
+    /// Bilinear sample at texture coordinates in [0, 1].
+    fn sample(&self, u: f32, v: f32) -> [f32; 3] {
+        // texel centers are at half integers
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let (a, b) = (self.texel(x0, y0), self.texel(x0 + 1, y0));
+        let (c, d) = (self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1));
+        [0, 1, 2].map(|i| {
+            let top = a[i] + (b[i] - a[i]) * fx;
+            let bottom = c[i] + (d[i] - c[i]) * fx;
+            top + (bottom - top) * fy
+        })
+    }
+}
+
+/// Renders a `width` x `height` buffer with a fragment function of the
+/// texture coordinates and the half texel size of the source.
+fn pass(
+    source: &Image,
+    width: u32,
+    height: u32,
+    fragment: impl Fn(&Image, f32, f32, f32, f32) -> [f32; 3],
+) -> Image {
+    let (hx, hy) = (0.5 / source.width as f32, 0.5 / source.height as f32);
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let u = (x as f32 + 0.5) / width as f32;
+            let v = (y as f32 + 0.5) / height as f32;
+            fragment(source, u, v, hx, hy)
+        })
+        .collect();
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+// ref: https://community.arm.com/cfs-file/__key/communityserver-blogs-components-weblogfiles/00-00-00-20-66/siggraph2015_2D00_mmg_2D00_marius_2D00_notes.pdf
+fn downsample(source: &Image, width: u32, height: u32, threshold: f32) -> Image {
+    pass(source, width, height, |image, u, v, hx, hy| {
+        let taps = [
+            (0.0, 0.0, 4.0),
+            (-hx, -hy, 1.0),
+            (hx, hy, 1.0),
+            (hx, -hy, 1.0),
+            (-hx, hy, 1.0),
+        ];
+        weighted_sum(image, u, v, &taps, 8.0).map(|c| (c - threshold).max(0.0))
+    })
+}
+
+fn upsample(source: &Image, width: u32, height: u32) -> Image {
+    pass(source, width, height, |image, u, v, hx, hy| {
+        let taps = [
+            (-2.0 * hx, 0.0, 1.0),
+            (-hx, hy, 2.0),
+            (0.0, 2.0 * hy, 1.0),
+            (hx, hy, 2.0),
+            (2.0 * hx, 0.0, 1.0),
+            (hx, -hy, 2.0),
+            (0.0, -2.0 * hy, 1.0),
+            (-hx, -hy, 2.0),
+        ];
+        weighted_sum(image, u, v, &taps, 12.0)
+    })
+}
+
+fn weighted_sum(image: &Image, u: f32, v: f32, taps: &[(f32, f32, f32)], total: f32) -> [f32; 3] {
+    taps.iter().fold([0.0; 3], |sum, &(du, dv, weight)| {
+        let s = image.sample(u + du, v + dv);
+        [0, 1, 2].map(|c| sum[c] + s[c] * weight / total)
+    })
+}
@@ -0,0 +1,461 @@
+extern crate nalgebra_glm as glm;
+
+use black_body::cvd::Cvd;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+use super::hdr::Hdr;
+use super::{
+    cvd_matrix, disc_attributes, ribbon_offset, sprite_diameter, sprite_falloff, upload_bytes,
+    ByteCounter, DrawMode, FrameUpload, Renderer, IDENTITY,
+};
+use crate::camera::Camera;
+use crate::fire::Fire;
+use crate::frame::{FrameData, Indices, Instances};
+
+/// Pure Rust rasterizer reproducing the WebGL pipeline without a GPU.
+///
+/// Segments go through the same MVP matrix, are clipped against the view
+/// volume, and are drawn one pixel wide like `GL_LINES`, with perspective
+/// correct vertex colors and a `LEQUAL` depth test. The sprite and ribbon
+/// modes draw discs and triangles shaded like their shaders; triangles
+/// crossing the near plane are dropped rather than clipped. The discs of the
+/// frame are drawn as sprites in every mode.
+///
+/// With HDR on, it instead adds the segments into a float radiance buffer
+/// without depth testing, and resolves it with the CPU reference of the
+/// bloom and tone mapping passes.
+///
+/// It counts the bytes the WebGL backend would upload for the same calls.
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    color: Vec<[f32; 4]>,
+    depth: Vec<f32>,
+    radiance: Vec<[f32; 3]>,
+    cvd: Option<Cvd>,
+    hdr: Option<Hdr>,
+    mode: DrawMode,
+    camera: Camera,
+    bytes: ByteCounter,
+}
+
+struct Vertex {
+    clip: glm::Vec4,
+    color: [f32; 4],
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            color: vec![[0.0, 0.0, 0.0, 1.0]; size],
+            depth: vec![1.0; size],
+            radiance: vec![[0.0; 3]; size],
+            cvd: None,
+            hdr: None,
+            mode: DrawMode::default(),
+            camera: Camera::default(),
+            bytes: ByteCounter::default(),
+        }
+    }
+
+    /// Reallocates the buffers for another output size, cleared.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        *self = Self {
+            cvd: self.cvd,
+            hdr: self.hdr,
+            mode: self.mode,
+            camera: self.camera.clone(),
+            bytes: self.bytes,
+            ..Self::new(width, height)
+        };
+    }
+
+    pub fn set_mode(&mut self, mode: DrawMode) {
+        self.mode = mode;
+    }
+
+    /// Post-processes the output to simulate a color vision deficiency.
+    pub fn set_cvd(&mut self, cvd: Option<Cvd>) {
+        self.cvd = cvd;
+    }
+
+    /// Switches to additive blending into a float buffer, with bloom and
+    /// tone mapping; `None` draws opaque lines straight to the output.
+    pub fn set_hdr(&mut self, hdr: Option<Hdr>) {
+        self.hdr = hdr;
+    }
+
+    /// Vertex, instance and index bytes the WebGL backend would upload.
+    pub fn bytes(&self) -> ByteCounter {
+        self.bytes
+    }
+
+    /// Counts `frame` as the WebGL backend uploads it in the current mode.
+    fn count(&mut self, frame: &FrameData) {
+        self.bytes.add(FrameUpload::new(frame, self.mode).bytes());
+        let discs = disc_attributes(&frame.discs);
+        let discs = discs.iter().map(|d| &d[..]).collect::<Vec<_>>();
+        self.bytes.add(upload_bytes(&discs, &Indices::default()));
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Output color of the pixel, with (0, 0) at the top left; linear, or
+    /// sRGB encoded with HDR on.
+    pub fn pixel(&self, x: u32, y: u32) -> [f32; 4] {
+        self.color[(y * self.width + x) as usize]
+    }
+
+    /// Light added into the pixel before post-processing; only written with
+    /// HDR on.
+    pub fn radiance(&self, x: u32, y: u32) -> [f32; 3] {
+        self.radiance[(y * self.width + x) as usize]
+    }
+
+    /// The color buffer as 8 bit RGBA rows, top to bottom.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.color
+            .iter()
+            .flat_map(|c| c.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect()
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let file = File::create(path)?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba8())?;
+        Ok(())
+    }
+
+    /// Simulates `frames` frames and writes them as `frame_0000.png`, ...
+    pub fn render_sequence<P: AsRef<Path>>(
+        &mut self,
+        fire: &mut Fire,
+        frames: usize,
+        directory: P,
+    ) -> Result<(), Box<dyn Error>> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+        for i in 0..frames {
+            let frame = fire.update();
+            self.render(&frame)?;
+            self.save_png(directory.join(format!("frame_{i:04}.png")))?;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.color.fill([0.0, 0.0, 0.0, 1.0]);
+        self.depth.fill(1.0);
+        self.radiance.fill([0.0; 3]);
+    }
+
+    fn vertex(positions: &[f32], colors: &[f32], mvp: &glm::Mat4, index: usize) -> Vertex {
+        let p = &positions[3 * index..3 * index + 3];
+        let c = &colors[4 * index..4 * index + 4];
+        Vertex {
+            clip: mvp * glm::Vec4::new(p[0], p[1], p[2], 1.0),
+            color: [c[0], c[1], c[2], c[3]],
+        }
+    }
+
+    // viewport transform, with y flipped so that row 0 is the top
+    fn window(&self, clip: &glm::Vec4) -> [f32; 3] {
+        let ndc = clip.xyz() / clip.w;
+        [
+            (ndc.x + 1.0) * 0.5 * self.width as f32,
+            (1.0 - ndc.y) * 0.5 * self.height as f32,
+            (ndc.z + 1.0) * 0.5,
+        ]
+    }
+
+    /// Blends a fragment into the pixel: added up with HDR on, or depth
+    /// tested and written otherwise.
+    fn fragment(&mut self, index: usize, z: f32, color: [f32; 4], cvd: &[[f32; 3]; 3]) {
+        // light adds up, so nothing occludes
+        if self.hdr.is_some() {
+            let sum = &mut self.radiance[index];
+            *sum = [0, 1, 2].map(|c| sum[c] + color[c] * color[3]);
+            return;
+        }
+
+        if z > self.depth[index] {
+            return;
+        }
+        let rgb = cvd.map(|row| row[0] * color[0] + row[1] * color[1] + row[2] * color[2]);
+        self.depth[index] = z;
+        self.color[index] = [rgb[0], rgb[1], rgb[2], color[3]];
+    }
+
+    fn draw_line(&mut self, a: &Vertex, b: &Vertex, cvd: &[[f32; 3]; 3]) {
+        let Some((t0, t1)) = Self::clip(&a.clip, &b.clip) else {
+            return;
+        };
+        let lerp = |t: f32| Vertex {
+            clip: a.clip + (b.clip - a.clip) * t,
+            color: [0, 1, 2, 3].map(|i| a.color[i] + (b.color[i] - a.color[i]) * t),
+        };
+        let (a, b) = (lerp(t0), lerp(t1));
+
+        let (p0, p1) = (self.window(&a.clip), self.window(&b.clip));
+        let (w0, w1) = (1.0 / a.clip.w, 1.0 / b.clip.w);
+
+        let steps = (p1[0] - p0[0])
+            .abs()
+            .max((p1[1] - p0[1]).abs())
+            .round()
+            .max(1.0) as usize;
+        for i in 0..steps {
+            // sample at pixel centers along the major axis
+            let t = (i as f32 + 0.5) / steps as f32;
+            let x = p0[0] + (p1[0] - p0[0]) * t;
+            let y = p0[1] + (p1[1] - p0[1]) * t;
+            if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+                continue;
+            }
+            let index = (y as u32 * self.width + x as u32) as usize;
+
+            // perspective correct interpolation of the color
+            let w = w0 + (w1 - w0) * t;
+            let color = [0, 1, 2, 3]
+                .map(|c| (a.color[c] * w0 + (b.color[c] * w1 - a.color[c] * w0) * t) / w);
+            let z = p0[2] + (p1[2] - p0[2]) * t;
+            self.fragment(index, z, color, cvd);
+        }
+    }
+
+    /// Draws a square point of `GL_POINTS`, shaded like `sprite_fragment.glsl`.
+    fn draw_sprite(&mut self, vertex: &Vertex, radius: f32, scale: f32, cvd: &[[f32; 3]; 3]) {
+        let inside = (0..3).all(|axis| vertex.clip[axis].abs() <= vertex.clip.w);
+        if radius <= 0.0 || !inside {
+            return;
+        }
+        let (diameter, dim) = sprite_diameter(radius, scale, vertex.clip.w, self.height);
+        let center = self.window(&vertex.clip);
+        let (left, top) = (center[0] - 0.5 * diameter, center[1] - 0.5 * diameter);
+
+        let clamp = |x: f32, max: u32| (x.max(0.0) as u32).min(max);
+        let (x0, x1) = (
+            clamp(left, self.width),
+            clamp(left + diameter + 1.0, self.width),
+        );
+        let (y0, y1) = (
+            clamp(top, self.height),
+            clamp(top + diameter + 1.0, self.height),
+        );
+        for y in y0..y1 {
+            for x in x0..x1 {
+                // gl_PointCoord of the pixel center, mapped to [-1, 1]
+                let u = 2.0 * (x as f32 + 0.5 - left) / diameter - 1.0;
+                let v = 2.0 * (y as f32 + 0.5 - top) / diameter - 1.0;
+                if u.abs() > 1.0 || v.abs() > 1.0 {
+                    continue;
+                }
+                let Some(falloff) = sprite_falloff(u, v) else {
+                    continue;
+                };
+                let k = dim * falloff;
+                let color = [
+                    vertex.color[0] * k,
+                    vertex.color[1] * k,
+                    vertex.color[2] * k,
+                    vertex.color[3],
+                ];
+                let index = (y * self.width + x) as usize;
+                self.fragment(index, center[2], color, cvd);
+            }
+        }
+    }
+
+    /// Fills the pixels whose centers fall in the triangle, with perspective
+    /// correct colors.
+    fn draw_triangle(&mut self, vertices: [&Vertex; 3], cvd: &[[f32; 3]; 3]) {
+        if vertices.iter().any(|v| v.clip.z < -v.clip.w) {
+            return;
+        }
+        let p = vertices.map(|v| self.window(&v.clip));
+        let edge = |a: [f32; 3], b: [f32; 3], x: f32, y: f32| {
+            (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])
+        };
+        let area = edge(p[0], p[1], p[2][0], p[2][1]);
+        if area == 0.0 {
+            return;
+        }
+
+        let min = |axis: usize| p.iter().map(|q| q[axis]).fold(f32::MAX, f32::min);
+        let max = |axis: usize| p.iter().map(|q| q[axis]).fold(f32::MIN, f32::max);
+        let clamp = |x: f32, max: u32| (x.max(0.0) as u32).min(max);
+        let (x0, x1) = (clamp(min(0), self.width), clamp(max(0) + 1.0, self.width));
+        let (y0, y1) = (clamp(min(1), self.height), clamp(max(1) + 1.0, self.height));
+        let w = vertices.map(|v| 1.0 / v.clip.w);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                // barycentric weights, positive inside whatever the winding
+                let b = [
+                    edge(p[1], p[2], cx, cy) / area,
+                    edge(p[2], p[0], cx, cy) / area,
+                    edge(p[0], p[1], cx, cy) / area,
+                ];
+                if b.iter().any(|&b| b < 0.0) {
+                    continue;
+                }
+                let z = b[0] * p[0][2] + b[1] * p[1][2] + b[2] * p[2][2];
+                let weights = [0, 1, 2].map(|i| b[i] * w[i]);
+                let total = weights[0] + weights[1] + weights[2];
+                let color = [0, 1, 2, 3].map(|c| {
+                    (0..3)
+                        .map(|i| weights[i] * vertices[i].color[c])
+                        .sum::<f32>()
+                        / total
+                });
+                let index = (y * self.width + x) as usize;
+                self.fragment(index, z, color, cvd);
+            }
+        }
+    }
+
+    /// Liang–Barsky clipping against -w <= x, y, z <= w; returns the visible
+    /// parameter range of the segment.
+    fn clip(a: &glm::Vec4, b: &glm::Vec4) -> Option<(f32, f32)> {
+        let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+        for axis in 0..3 {
+            for sign in [1.0, -1.0] {
+                // distance to the plane, positive inside
+                let da = a.w - sign * a[axis];
+                let db = b.w - sign * b[axis];
+                if da < 0.0 && db < 0.0 {
+                    return None;
+                }
+                let t = da / (da - db);
+                if da < 0.0 {
+                    t0 = t0.max(t);
+                } else if db < 0.0 {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+        (t0 <= t1).then_some((t0, t1))
+    }
+
+    fn draw(&mut self, frame: &FrameData) -> Result<(), String> {
+        let vertex_count = frame.vertex_count();
+        if frame.colors.len() != 4 * vertex_count {
+            return Err(format!(
+                "expected {} color components, but got {}",
+                4 * vertex_count,
+                frame.colors.len()
+            ));
+        }
+
+        let mvp = self.camera.mvp_matrix(self.width, self.height);
+        let eye = self.camera.eye();
+        // with HDR, the deficiency is simulated after tone mapping
+        let cvd = cvd_matrix(self.cvd);
+        let line_cvd = if self.hdr.is_some() { IDENTITY } else { cvd };
+
+        if let Some(i) = frame.segments.iter().find(|&i| i as usize >= vertex_count) {
+            return Err(format!("index out of range: {i}"));
+        }
+
+        self.clear();
+        let (positions, colors) = (&frame.positions, &frame.colors);
+        match self.mode {
+            DrawMode::Lines => {
+                let mut indices = frame.segments.iter();
+                while let (Some(i), Some(j)) = (indices.next(), indices.next()) {
+                    let a = Self::vertex(positions, colors, &mvp, i as usize);
+                    let b = Self::vertex(positions, colors, &mvp, j as usize);
+                    self.draw_line(&a, &b, &line_cvd);
+                }
+            }
+            DrawMode::Sprites { scale } => {
+                for (i, &radius) in frame.padded_sizes().iter().enumerate() {
+                    let v = Self::vertex(positions, colors, &mvp, i);
+                    self.draw_sprite(&v, radius, scale, &line_cvd);
+                }
+            }
+            DrawMode::Ribbons { width } => {
+                let ribbons = frame.ribbons();
+                let positions = (0..ribbons.vertex_count())
+                    .flat_map(|i| {
+                        let at = |v: &[f32]| [v[3 * i], v[3 * i + 1], v[3 * i + 2]];
+                        let position = at(&ribbons.positions);
+                        let tangent = at(&ribbons.tangents);
+                        let offset = ribbon_offset(position, tangent, ribbons.sides[i], width, eye);
+                        [0, 1, 2].map(|c| position[c] + offset[c])
+                    })
+                    .collect::<Vec<_>>();
+                let mut indices = ribbons.triangles.iter();
+                while let (Some(i), Some(j), Some(k)) =
+                    (indices.next(), indices.next(), indices.next())
+                {
+                    let [a, b, c] = [i, j, k]
+                        .map(|i| Self::vertex(&positions, &ribbons.colors, &mvp, i as usize));
+                    self.draw_triangle([&a, &b, &c], &line_cvd);
+                }
+            }
+        }
+
+        for disc in frame.discs.iter() {
+            let [x, y, z] = disc.center;
+            let vertex = Vertex {
+                clip: mvp * glm::Vec4::new(x, y, z, 1.0),
+                color: disc.color,
+            };
+            self.draw_sprite(&vertex, disc.radius, 1.0, &line_cvd);
+        }
+
+        if let Some(hdr) = self.hdr {
+            let display = hdr.resolve(&self.radiance, self.width, self.height, &cvd);
+            for (pixel, rgb) in self.color.iter_mut().zip(display) {
+                *pixel = [rgb[0], rgb[1], rgb[2], 1.0];
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    type Error = String;
+
+    fn set_camera(&mut self, camera: &Camera) {
+        self.camera = camera.clone();
+    }
+
+    fn render(&mut self, frame: &FrameData) -> Result<(), Self::Error> {
+        self.bytes.begin_frame();
+        self.count(frame);
+        self.draw(frame)
+    }
+
+    fn render_instanced(
+        &mut self,
+        instances: &Instances,
+        frame: &FrameData,
+    ) -> Result<(), Self::Error> {
+        self.bytes.begin_frame();
+        self.bytes
+            .add(upload_bytes(&[&instances.data], &Indices::default()));
+        self.count(frame);
+        let mut expanded = instances.frame();
+        expanded.extend(frame);
+        self.draw(&expanded)
+    }
+}
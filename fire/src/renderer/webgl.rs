@@ -0,0 +1,614 @@
+mod hdr;
+mod instanced;
+mod simulation;
+
+use black_body::cvd::Cvd;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{WebGl2RenderingContext as GL, *};
+
+use super::hdr::Hdr;
+use super::{
+    cvd_matrix, disc_attributes, drawing_buffer_size, grow_capacity, pixels_per_meter,
+    upload_bytes, ByteCounter, DrawMode, FrameUpload, Renderer, IDENTITY,
+};
+use crate::camera::Camera;
+use crate::fire::kernel::KernelSimulation;
+use crate::frame::{Disc, FrameData, Indices, Instances};
+use crate::shader::*;
+use hdr::HdrPass;
+use instanced::InstancedPass;
+use simulation::SimulationPass;
+
+pub struct WebGlRenderer {
+    pub gl: GL,
+    canvas: HtmlCanvasElement,
+    // of the drawing buffer [px]
+    width: u32,
+    height: u32,
+    // that the drawing buffer was sized for
+    device_pixel_ratio: f64,
+    pub shader_program: WebGlProgram,
+    sprite_program: WebGlProgram,
+    ribbon_program: WebGlProgram,
+    // positions, colors and sizes of the frame
+    buffers: Buffers,
+    // positions, colors, tangents and sides of the ribbons
+    ribbon_buffers: Buffers,
+    // centers, colors and radii of the discs
+    disc_buffers: Buffers,
+    instanced: InstancedPass,
+    // particle pool of the GPU simulation, once one has stepped
+    simulation: Option<SimulationPass>,
+    cvd: Option<Cvd>,
+    hdr: Option<HdrPass>,
+    mode: DrawMode,
+    camera: Camera,
+    bytes: ByteCounter,
+}
+
+/// What the passes need of the camera and the viewport.
+struct View {
+    // column major
+    mvp: Vec<f32>,
+    eye: [f32; 3],
+    // of the viewport [px]
+    height: u32,
+}
+
+/// Vertex array and buffers, allocated once and updated in place.
+struct Buffers {
+    vao: WebGlVertexArrayObject,
+    // one per attribute location, with its number of components
+    attributes: Vec<(WebGlBuffer, i32)>,
+    indices: WebGlBuffer,
+    // capacities in vertices and in bytes of indices
+    vertex_capacity: usize,
+    index_capacity: usize,
+}
+
+impl WebGlRenderer {
+    /// Draws into the `canvas` element, at its laid out size in device
+    /// pixels.
+    pub fn new() -> Result<Self, JsValue> {
+        let window = Self::init_window()?;
+        let document = Self::init_document(&window)?;
+        let canvas = Self::init_canvas(&document)?;
+        let gl = Self::init_gl(&canvas)?;
+        let shader_program = Self::init_program(
+            &gl,
+            include_str!("../shader/vertex.glsl"),
+            include_str!("../shader/fragment.glsl"),
+        )?;
+        let sprite_program = Self::init_program(
+            &gl,
+            include_str!("../shader/sprite_vertex.glsl"),
+            include_str!("../shader/sprite_fragment.glsl"),
+        )?;
+        let ribbon_program = Self::init_program(
+            &gl,
+            include_str!("../shader/ribbon_vertex.glsl"),
+            include_str!("../shader/fragment.glsl"),
+        )?;
+        let buffers = Buffers::new(&gl, &[3, 4, 1])?;
+        let ribbon_buffers = Buffers::new(&gl, &[3, 4, 3, 1])?;
+        let disc_buffers = Buffers::new(&gl, &[3, 4, 1])?;
+        let instanced = InstancedPass::new(&gl)?;
+
+        let mut renderer = Self {
+            gl,
+            canvas,
+            width: 0,
+            height: 0,
+            device_pixel_ratio: 0.0,
+            shader_program,
+            sprite_program,
+            ribbon_program,
+            buffers,
+            ribbon_buffers,
+            disc_buffers,
+            instanced,
+            simulation: None,
+            cvd: None,
+            hdr: None,
+            mode: DrawMode::default(),
+            camera: Camera::default(),
+            bytes: ByteCounter::default(),
+        };
+        renderer.fit_to_canvas()?;
+        Ok(renderer)
+    }
+
+    pub fn canvas(&self) -> &HtmlCanvasElement {
+        &self.canvas
+    }
+
+    pub fn device_pixel_ratio(&self) -> f64 {
+        self.device_pixel_ratio
+    }
+
+    /// Resizes the drawing buffer to the canvas's laid out size times the
+    /// device pixel ratio, if either changed.
+    pub fn fit_to_canvas(&mut self) -> Result<(), JsValue> {
+        let window = Self::init_window()?;
+        let device_pixel_ratio = window.device_pixel_ratio();
+        // float targets are textures, and the largest viewport may be larger
+        let max_size = self
+            .gl
+            .get_parameter(GL::MAX_TEXTURE_SIZE)?
+            .as_f64()
+            .unwrap_or(4096.0) as u32;
+        let (width, height) = drawing_buffer_size(
+            self.canvas.client_width() as f64,
+            self.canvas.client_height() as f64,
+            device_pixel_ratio,
+            max_size,
+        );
+        self.device_pixel_ratio = device_pixel_ratio;
+        if (width, height) != (self.width, self.height) {
+            self.resize(width, height)?;
+        }
+        Ok(())
+    }
+
+    /// Resizes the drawing buffer and everything sized after it; the
+    /// projection follows the new aspect ratio from the next frame.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), JsValue> {
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+        self.width = width;
+        self.height = height;
+        let settings = self.hdr.as_ref().map(|pass| pass.settings);
+        if settings.is_some() {
+            self.set_hdr(settings)?;
+        }
+        Ok(())
+    }
+
+    /// Post-processes the output to simulate a color vision deficiency.
+    pub fn set_cvd(&mut self, cvd: Option<Cvd>) {
+        self.cvd = cvd;
+    }
+
+    pub fn set_mode(&mut self, mode: DrawMode) {
+        self.mode = mode;
+    }
+
+    /// Vertex, instance and index bytes uploaded; textures and the slots of
+    /// the GPU simulation are left out, as they are uploaded once.
+    pub fn bytes(&self) -> ByteCounter {
+        self.bytes
+    }
+
+    /// Switches to additive blending into a float buffer, with bloom and
+    /// tone mapping; `None` draws opaque lines straight to the canvas.
+    ///
+    /// Fails, leaving the current path, if the float render target is not
+    /// supported.
+    pub fn set_hdr(&mut self, hdr: Option<Hdr>) -> Result<(), JsValue> {
+        let pass = hdr
+            .map(|settings| HdrPass::new(&self.gl, settings, self.width, self.height))
+            .transpose()?;
+        if let Some(old) = std::mem::replace(&mut self.hdr, pass) {
+            old.delete(&self.gl);
+        }
+        Ok(())
+    }
+
+    fn init_window() -> Result<Window, JsValue> {
+        web_sys::window().ok_or_else(|| JsValue::from_str("Failed to get window"))
+    }
+
+    fn init_document(window: &Window) -> Result<Document, JsValue> {
+        window
+            .document()
+            .ok_or_else(|| JsValue::from_str("Failed to get document"))
+    }
+
+    fn init_canvas(document: &Document) -> Result<HtmlCanvasElement, JsValue> {
+        let canvas = document
+            .get_element_by_id("canvas")
+            .map(|e| e.dyn_into::<HtmlCanvasElement>())
+            .ok_or_else(|| JsValue::from_str("Failed to get canvas"))??;
+        Ok(canvas)
+    }
+
+    fn init_gl(canvas: &HtmlCanvasElement) -> Result<GL, JsValue> {
+        canvas
+            .get_context("webgl2")
+            .and_then(|op| {
+                op.ok_or_else(|| JsValue::from_str("Failed to get WebGl2RenderingContext"))
+            })
+            .and_then(|context| {
+                context.dyn_into::<GL>().map_err(|_| {
+                    JsValue::from_str("Failed to cast context to WebGl2RenderingContext")
+                })
+            })
+    }
+
+    fn init_program(
+        gl: &GL,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> Result<WebGlProgram, JsValue> {
+        let vertex_shader = create_shader(gl, GL::VERTEX_SHADER, vertex_source)?;
+        let fragment_shader = create_shader(gl, GL::FRAGMENT_SHADER, fragment_source)?;
+        let program = link_program(gl, &vertex_shader, &fragment_shader)?;
+        Ok(program)
+    }
+
+    fn uniform_location(
+        &self,
+        program: &WebGlProgram,
+        name: &str,
+    ) -> Result<WebGlUniformLocation, JsValue> {
+        self.gl
+            .get_uniform_location(program, name)
+            .ok_or_else(|| JsValue::from_str(&format!("Failed to get uniform location {name}")))
+    }
+
+    fn mvp_array(&self) -> Vec<f32> {
+        let mvp_matrix = self.camera.mvp_matrix(self.width, self.height);
+        let mvp_arrays: [[f32; 4]; 4] = mvp_matrix.into();
+        mvp_arrays.iter().flat_map(|a| *a).collect()
+    }
+
+    fn view(&self) -> View {
+        View {
+            mvp: self.mvp_array(),
+            eye: self.camera.eye(),
+            height: self.height,
+        }
+    }
+
+    fn send_mvp_matrix(&self, location: &WebGlUniformLocation) {
+        let mvp_matrices = self.mvp_array();
+
+        self.gl
+            .uniform_matrix4fv_with_f32_array_and_src_offset_and_src_length(
+                Some(location),
+                false,
+                &mvp_matrices,
+                0,
+                0,
+            );
+    }
+
+    fn send_cvd_matrix(&self, location: &WebGlUniformLocation, matrix: &[[f32; 3]; 3]) {
+        self.gl
+            .uniform_matrix3fv_with_f32_array(Some(location), false, &column_major(matrix));
+    }
+
+    fn begin(&self) {
+        if let Some(hdr) = &self.hdr {
+            hdr.begin_scene(&self.gl);
+            return;
+        }
+        self.gl
+            .viewport(0, 0, self.width as i32, self.height as i32);
+        self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        self.gl.clear_depth(1.0);
+        self.gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        self.gl.disable(GL::BLEND);
+        self.gl.enable(GL::DEPTH_TEST);
+        self.gl.depth_func(GL::LEQUAL);
+    }
+
+    fn end(&self) {
+        if let Some(hdr) = &self.hdr {
+            let cvd = cvd_matrix(self.cvd);
+            hdr.resolve(&self.gl, self.width, self.height, &column_major(&cvd));
+        }
+        self.gl.flush();
+    }
+
+    // with HDR, the deficiency is simulated after tone mapping
+    fn line_cvd(&self) -> [[f32; 3]; 3] {
+        match self.hdr {
+            Some(_) => IDENTITY,
+            None => cvd_matrix(self.cvd),
+        }
+    }
+
+    fn draw_frame(&mut self, frame: &FrameData) -> Result<(), JsValue> {
+        let program = match self.mode {
+            DrawMode::Lines => self.shader_program.clone(),
+            DrawMode::Sprites { .. } => self.sprite_program.clone(),
+            DrawMode::Ribbons { .. } => self.ribbon_program.clone(),
+        };
+        self.gl.use_program(Some(&program));
+
+        let upload = FrameUpload::new(frame, self.mode);
+        let buffers = match self.mode {
+            DrawMode::Ribbons { .. } => &mut self.ribbon_buffers,
+            _ => &mut self.buffers,
+        };
+        let bytes = buffers.upload(
+            &self.gl,
+            upload.vertex_count,
+            &upload.slices(),
+            &upload.indices,
+        );
+        self.bytes.add(bytes);
+
+        // 視点を定義
+        self.send_mvp_matrix(&self.uniform_location(&program, "mvpMatrix")?);
+        self.send_cvd_matrix(
+            &self.uniform_location(&program, "cvdMatrix")?,
+            &self.line_cvd(),
+        );
+
+        // 描画
+        match self.mode {
+            DrawMode::Ribbons { width } => {
+                let location = self.uniform_location(&program, "eye")?;
+                let [x, y, z] = self.camera.eye();
+                self.gl.uniform3f(Some(&location), x, y, z);
+                let location = self.uniform_location(&program, "width")?;
+                self.gl.uniform1f(Some(&location), width);
+                // the quads face the camera either way round
+                self.gl.disable(GL::CULL_FACE);
+                self.gl.draw_elements_with_i32(
+                    GL::TRIANGLES,
+                    upload.indices.len() as i32,
+                    index_type(&upload.indices),
+                    0,
+                );
+            }
+            DrawMode::Sprites { scale } => {
+                let location = self.uniform_location(&program, "pixelsPerMeter")?;
+                self.gl
+                    .uniform1f(Some(&location), pixels_per_meter(self.height));
+                let location = self.uniform_location(&program, "scale")?;
+                self.gl.uniform1f(Some(&location), scale);
+                self.gl
+                    .draw_arrays(GL::POINTS, 0, frame.vertex_count() as i32);
+            }
+            DrawMode::Lines => {
+                self.gl.enable(GL::CULL_FACE);
+                self.gl.draw_elements_with_i32(
+                    GL::LINES,
+                    upload.indices.len() as i32,
+                    index_type(&upload.indices),
+                    0,
+                );
+            }
+        }
+        self.draw_discs(&frame.discs)
+    }
+
+    /// Draws the discs as sprites at their own size, whatever the mode.
+    fn draw_discs(&mut self, discs: &[Disc]) -> Result<(), JsValue> {
+        if discs.is_empty() {
+            return Ok(());
+        }
+        let program = self.sprite_program.clone();
+        self.gl.use_program(Some(&program));
+
+        let [centers, colors, radii] = disc_attributes(discs);
+        let bytes = self.disc_buffers.upload(
+            &self.gl,
+            discs.len(),
+            &[&centers, &colors, &radii],
+            &Indices::default(),
+        );
+        self.bytes.add(bytes);
+
+        self.send_mvp_matrix(&self.uniform_location(&program, "mvpMatrix")?);
+        self.send_cvd_matrix(
+            &self.uniform_location(&program, "cvdMatrix")?,
+            &self.line_cvd(),
+        );
+        let location = self.uniform_location(&program, "pixelsPerMeter")?;
+        self.gl
+            .uniform1f(Some(&location), pixels_per_meter(self.height));
+        let location = self.uniform_location(&program, "scale")?;
+        self.gl.uniform1f(Some(&location), 1.0);
+        self.gl.draw_arrays(GL::POINTS, 0, discs.len() as i32);
+        Ok(())
+    }
+}
+
+impl Buffers {
+    /// Buffers for float attributes at locations 0, 1, ... with `sizes`
+    /// components each.
+    fn new(gl: &GL, sizes: &[i32]) -> Result<Self, JsValue> {
+        let vao = gl
+            .create_vertex_array()
+            .ok_or("Failed to create vertex array object")?;
+        let create_buffer = || gl.create_buffer().ok_or("Failed to create buffer");
+        let attributes = sizes
+            .iter()
+            .map(|&size| Ok((create_buffer()?, size)))
+            .collect::<Result<Vec<_>, JsValue>>()?;
+        let indices = create_buffer()?;
+
+        // the attribute layout never changes; only the data does
+        gl.bind_vertex_array(Some(&vao));
+        for (location, (buffer, size)) in attributes.iter().enumerate() {
+            gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+            gl.enable_vertex_attrib_array(location as u32);
+            gl.vertex_attrib_pointer_with_i32(location as u32, *size, GL::FLOAT, false, 0, 0);
+        }
+        gl.bind_buffer(GL::ELEMENT_ARRAY_BUFFER, Some(&indices));
+        gl.bind_vertex_array(None);
+
+        Ok(Self {
+            vao,
+            attributes,
+            indices,
+            vertex_capacity: 0,
+            index_capacity: 0,
+        })
+    }
+
+    /// Uploads one slice per attribute and the indices into the persistent
+    /// buffers, reallocating them only when they no longer fit, and returns
+    /// the bytes written.
+    fn upload(
+        &mut self,
+        gl: &GL,
+        vertex_count: usize,
+        data: &[&[f32]],
+        indices: &Indices,
+    ) -> usize {
+        gl.bind_vertex_array(Some(&self.vao));
+
+        let vertex_capacity = grow_capacity(self.vertex_capacity, vertex_count);
+        if vertex_capacity != self.vertex_capacity {
+            for (buffer, size) in self.attributes.iter() {
+                gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+                let bytes = vertex_capacity * *size as usize * std::mem::size_of::<f32>();
+                gl.buffer_data_with_i32(GL::ARRAY_BUFFER, bytes as i32, GL::DYNAMIC_DRAW);
+            }
+            self.vertex_capacity = vertex_capacity;
+        }
+        for ((buffer, _), data) in self.attributes.iter().zip(data) {
+            gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+            unsafe {
+                // the view must not outlive the borrow; nothing allocates in
+                // between
+                let view = js_sys::Float32Array::view(data);
+                gl.buffer_sub_data_with_i32_and_array_buffer_view(GL::ARRAY_BUFFER, 0, &view);
+            }
+        }
+
+        if indices.is_empty() {
+            return upload_bytes(data, indices);
+        }
+        let bytes = indices.len() * indices.stride();
+        let index_capacity = grow_capacity(self.index_capacity, bytes);
+        if index_capacity != self.index_capacity {
+            gl.buffer_data_with_i32(
+                GL::ELEMENT_ARRAY_BUFFER,
+                index_capacity as i32,
+                GL::DYNAMIC_DRAW,
+            );
+            self.index_capacity = index_capacity;
+        }
+        unsafe {
+            let view = match indices {
+                Indices::U16(indices) => js_sys::Uint16Array::view(indices).into(),
+                Indices::U32(indices) => js_sys::Uint32Array::view(indices).into(),
+            };
+            gl.buffer_sub_data_with_i32_and_array_buffer_view(GL::ELEMENT_ARRAY_BUFFER, 0, &view);
+        }
+
+        upload_bytes(data, indices)
+    }
+
+    fn delete(&self, gl: &GL) {
+        gl.delete_vertex_array(Some(&self.vao));
+        for (buffer, _) in self.attributes.iter() {
+            gl.delete_buffer(Some(buffer));
+        }
+        gl.delete_buffer(Some(&self.indices));
+    }
+}
+
+fn index_type(indices: &Indices) -> u32 {
+    match indices {
+        Indices::U16(_) => GL::UNSIGNED_SHORT,
+        Indices::U32(_) => GL::UNSIGNED_INT,
+    }
+}
+
+fn column_major(matrix: &[[f32; 3]; 3]) -> Vec<f32> {
+    (0..3)
+        .flat_map(|j| (0..3).map(move |i| matrix[i][j]))
+        .collect()
+}
+
+impl Renderer for WebGlRenderer {
+    type Error = JsValue;
+
+    fn set_camera(&mut self, camera: &Camera) {
+        self.camera = camera.clone();
+    }
+
+    fn render(&mut self, frame: &FrameData) -> Result<(), Self::Error> {
+        self.bytes.begin_frame();
+        self.begin();
+        self.draw_frame(frame)?;
+        self.end();
+        Ok(())
+    }
+
+    fn render_instanced(
+        &mut self,
+        instances: &Instances,
+        frame: &FrameData,
+    ) -> Result<(), Self::Error> {
+        self.bytes.begin_frame();
+        self.begin();
+        let bytes = self.instanced.upload(&self.gl, instances);
+        self.bytes.add(bytes);
+        self.instanced.draw(
+            &self.gl,
+            instances.len(),
+            self.mode,
+            &self.view(),
+            &column_major(&self.line_cvd()),
+        );
+        self.draw_frame(frame)?;
+        self.end();
+        Ok(())
+    }
+
+    fn simulate(&mut self, simulation: &mut KernelSimulation) -> Result<(), Self::Error> {
+        // a pool of another size is another simulation
+        if self.simulation.as_ref().map(|pass| pass.capacity()) != Some(simulation.capacity()) {
+            let pass = SimulationPass::new(&self.gl, simulation)?;
+            if let Some(old) = self.simulation.replace(pass) {
+                old.delete(&self.gl);
+            }
+        }
+        if let Some(pass) = self.simulation.as_mut() {
+            pass.step(&self.gl, &simulation.kernel);
+        }
+        simulation.kernel.advance();
+        Ok(())
+    }
+
+    fn render_simulation(
+        &mut self,
+        simulation: &KernelSimulation,
+        frame: &FrameData,
+    ) -> Result<(), Self::Error> {
+        let Some(pass) = self.simulation.as_ref() else {
+            return self.render_instanced(&simulation.instances(), frame);
+        };
+        self.bytes.begin_frame();
+        self.begin();
+        pass.draw(
+            &self.gl,
+            &self.instanced,
+            self.mode,
+            &self.view(),
+            &column_major(&self.line_cvd()),
+        );
+        self.draw_frame(frame)?;
+        self.end();
+        Ok(())
+    }
+}
+
+impl Drop for WebGlRenderer {
+    fn drop(&mut self) {
+        let gl = &self.gl;
+        if let Some(hdr) = self.hdr.take() {
+            hdr.delete(gl);
+        }
+        gl.bind_vertex_array(None);
+        self.buffers.delete(gl);
+        self.ribbon_buffers.delete(gl);
+        self.disc_buffers.delete(gl);
+        self.instanced.delete(gl);
+        if let Some(simulation) = self.simulation.take() {
+            simulation.delete(gl);
+        }
+        gl.delete_program(Some(&self.shader_program));
+        gl.delete_program(Some(&self.sprite_program));
+        gl.delete_program(Some(&self.ribbon_program));
+    }
+}
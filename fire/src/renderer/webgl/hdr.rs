@@ -0,0 +1,220 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGl2RenderingContext as GL, *};
+
+use crate::renderer::hdr::{Bloom, Hdr};
+use crate::shader::*;
+
+/// Float render target sampled by the following passes.
+struct Target {
+    texture: WebGlTexture,
+    framebuffer: WebGlFramebuffer,
+    width: u32,
+    height: u32,
+}
+
+/// Render targets and programs of the HDR path: the scene is added into a
+/// half float buffer, the bloom runs down and up a chain of smaller
+/// buffers, and the composite pass tone maps both to the canvas.
+pub struct HdrPass {
+    pub settings: Hdr,
+    scene: Target,
+    levels: Vec<Target>,
+    downsample: WebGlProgram,
+    upsample: WebGlProgram,
+    composite: WebGlProgram,
+    // fullscreen triangles need no attributes, but WebGL wants a bound VAO
+    vao: WebGlVertexArrayObject,
+}
+
+impl HdrPass {
+    pub fn new(gl: &GL, settings: Hdr, width: u32, height: u32) -> Result<Self, JsValue> {
+        // half floats can be sampled with filtering, but rendering to them
+        // is an extension
+        gl.get_extension("EXT_color_buffer_float")?
+            .ok_or("EXT_color_buffer_float is not supported")?;
+
+        let scene = Self::create_target(gl, width, height)?;
+        let levels = (0..settings.bloom.levels)
+            .map(|level| {
+                let (w, h) = Bloom::level_size(width, height, level);
+                Self::create_target(gl, w, h)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let fullscreen = include_str!("../../shader/fullscreen.glsl");
+        let program = |source: &str| {
+            let vertex_shader = create_shader(gl, GL::VERTEX_SHADER, fullscreen)?;
+            let fragment_shader = create_shader(gl, GL::FRAGMENT_SHADER, source)?;
+            link_program(gl, &vertex_shader, &fragment_shader)
+        };
+        let downsample = program(include_str!("../../shader/downsample.glsl"))?;
+        let upsample = program(include_str!("../../shader/upsample.glsl"))?;
+        let composite = program(include_str!("../../shader/composite.glsl"))?;
+        let vao = gl
+            .create_vertex_array()
+            .ok_or("Failed to create vertex array object")?;
+
+        Ok(Self {
+            settings,
+            scene,
+            levels,
+            downsample,
+            upsample,
+            composite,
+            vao,
+        })
+    }
+
+    fn create_target(gl: &GL, width: u32, height: u32) -> Result<Target, JsValue> {
+        let texture = gl.create_texture().ok_or("Failed to create texture")?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        gl.tex_storage_2d(GL::TEXTURE_2D, 1, GL::RGBA16F, width as i32, height as i32);
+        for (parameter, value) in [
+            (GL::TEXTURE_MIN_FILTER, GL::LINEAR),
+            (GL::TEXTURE_MAG_FILTER, GL::LINEAR),
+            (GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE),
+            (GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameteri(GL::TEXTURE_2D, parameter, value as i32);
+        }
+
+        let framebuffer = gl
+            .create_framebuffer()
+            .ok_or("Failed to create framebuffer")?;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&framebuffer));
+        gl.framebuffer_texture_2d(
+            GL::FRAMEBUFFER,
+            GL::COLOR_ATTACHMENT0,
+            GL::TEXTURE_2D,
+            Some(&texture),
+            0,
+        );
+        let status = gl.check_framebuffer_status(GL::FRAMEBUFFER);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        if status != GL::FRAMEBUFFER_COMPLETE {
+            return Err(JsValue::from_str(&format!(
+                "Incomplete framebuffer: 0x{status:x}"
+            )));
+        }
+
+        Ok(Target {
+            texture,
+            framebuffer,
+            width,
+            height,
+        })
+    }
+
+    /// Binds the scene buffer, cleared, for lines to be added into.
+    pub fn begin_scene(&self, gl: &GL) {
+        Self::bind_target(gl, Some(&self.scene));
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+
+        gl.disable(GL::DEPTH_TEST);
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::ONE, GL::ONE);
+    }
+
+    /// Runs the bloom and composites the scene onto the canvas.
+    pub fn resolve(&self, gl: &GL, width: u32, height: u32, cvd_matrix: &[f32]) {
+        gl.bind_vertex_array(Some(&self.vao));
+        let bloom = self.settings.bloom;
+        let levels = if bloom.is_enabled() {
+            &self.levels[..]
+        } else {
+            &[]
+        };
+
+        gl.disable(GL::BLEND);
+        gl.use_program(Some(&self.downsample));
+        for (level, target) in levels.iter().enumerate() {
+            let source = if level == 0 {
+                &self.scene
+            } else {
+                &levels[level - 1]
+            };
+            // only the first pass cuts off the dim part
+            let threshold = if level == 0 { bloom.threshold } else { 0.0 };
+            gl.uniform1f(
+                gl.get_uniform_location(&self.downsample, "threshold")
+                    .as_ref(),
+                threshold,
+            );
+            self.filter(gl, &self.downsample, source, target);
+        }
+
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::ONE, GL::ONE);
+        gl.use_program(Some(&self.upsample));
+        for level in (1..levels.len()).rev() {
+            self.filter(gl, &self.upsample, &levels[level], &levels[level - 1]);
+        }
+        gl.disable(GL::BLEND);
+
+        Self::bind_target(gl, None);
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.use_program(Some(&self.composite));
+        let location = |name| gl.get_uniform_location(&self.composite, name);
+        Self::bind_texture(gl, 0, &self.scene);
+        gl.uniform1i(location("scene").as_ref(), 0);
+        // without levels, the scene stands in for the glow at no weight
+        Self::bind_texture(gl, 1, levels.first().unwrap_or(&self.scene));
+        gl.uniform1i(location("bloom").as_ref(), 1);
+        let intensity = if levels.is_empty() {
+            0.0
+        } else {
+            bloom.intensity
+        };
+        gl.uniform1f(location("bloomIntensity").as_ref(), intensity);
+        gl.uniform1f(location("exposure").as_ref(), self.settings.exposure);
+        gl.uniform1i(
+            location("toneMapping").as_ref(),
+            self.settings.tone_mapping.index(),
+        );
+        gl.uniform_matrix3fv_with_f32_array(location("cvdMatrix").as_ref(), false, cvd_matrix);
+        gl.draw_arrays(GL::TRIANGLES, 0, 3);
+
+        // the scene must not be bound while the next frame renders into it
+        for unit in [1, 0] {
+            gl.active_texture(GL::TEXTURE0 + unit);
+            gl.bind_texture(GL::TEXTURE_2D, None);
+        }
+    }
+
+    /// Draws `target` with the fullscreen program sampling `source`.
+    fn filter(&self, gl: &GL, program: &WebGlProgram, source: &Target, target: &Target) {
+        Self::bind_target(gl, Some(target));
+        Self::bind_texture(gl, 0, source);
+        gl.uniform1i(gl.get_uniform_location(program, "source").as_ref(), 0);
+        gl.uniform2f(
+            gl.get_uniform_location(program, "halfTexel").as_ref(),
+            0.5 / source.width as f32,
+            0.5 / source.height as f32,
+        );
+        gl.draw_arrays(GL::TRIANGLES, 0, 3);
+    }
+
+    fn bind_target(gl: &GL, target: Option<&Target>) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, target.map(|t| &t.framebuffer));
+        if let Some(target) = target {
+            gl.viewport(0, 0, target.width as i32, target.height as i32);
+        }
+    }
+
+    fn bind_texture(gl: &GL, unit: u32, target: &Target) {
+        gl.active_texture(GL::TEXTURE0 + unit);
+        gl.bind_texture(GL::TEXTURE_2D, Some(&target.texture));
+    }
+
+    pub fn delete(&self, gl: &GL) {
+        for target in std::iter::once(&self.scene).chain(&self.levels) {
+            gl.delete_framebuffer(Some(&target.framebuffer));
+            gl.delete_texture(Some(&target.texture));
+        }
+        gl.delete_program(Some(&self.downsample));
+        gl.delete_program(Some(&self.upsample));
+        gl.delete_program(Some(&self.composite));
+        gl.delete_vertex_array(Some(&self.vao));
+    }
+}
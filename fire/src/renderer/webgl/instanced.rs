@@ -0,0 +1,195 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGl2RenderingContext as GL, *};
+
+use super::View;
+use crate::frame::{Indices, Instances, INSTANCE_FLOATS};
+use crate::palette::Palette;
+use crate::renderer::{grow_capacity, pixels_per_meter, upload_bytes, DrawMode};
+use crate::shader::*;
+
+/// Instanced drawing of sparks: one interleaved buffer of per-spark
+/// attributes, expanded into lines, sprites or ribbons and colored from a
+/// black body table in the vertex shader.
+pub struct InstancedPass {
+    program: WebGlProgram,
+    sprite_program: WebGlProgram,
+    vao: WebGlVertexArrayObject,
+    buffer: WebGlBuffer,
+    // in instances
+    capacity: usize,
+    lut: WebGlTexture,
+}
+
+impl InstancedPass {
+    pub fn new(gl: &GL) -> Result<Self, JsValue> {
+        let vertex_source = include_str!("../../shader/instance_vertex.glsl");
+        let program = |fragment_source: &str| {
+            let vertex_shader = create_shader(gl, GL::VERTEX_SHADER, vertex_source)?;
+            let fragment_shader = create_shader(gl, GL::FRAGMENT_SHADER, fragment_source)?;
+            link_program(gl, &vertex_shader, &fragment_shader)
+        };
+        let sprite_program = program(include_str!("../../shader/sprite_fragment.glsl"))?;
+        let program = program(include_str!("../../shader/fragment.glsl"))?;
+
+        let vao = gl
+            .create_vertex_array()
+            .ok_or("Failed to create vertex array object")?;
+        let buffer = gl.create_buffer().ok_or("Failed to create buffer")?;
+        gl.bind_vertex_array(Some(&vao));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&buffer));
+        Self::bind_attributes(gl, INSTANCE_FLOATS, true);
+        gl.bind_vertex_array(None);
+
+        Ok(Self {
+            program,
+            sprite_program,
+            vao,
+            buffer,
+            capacity: 0,
+            lut: Self::create_lut(gl)?,
+        })
+    }
+
+    /// Points the instance attributes of the bound vertex array at the bound
+    /// buffer, with `stride` words per instance: the position and previous
+    /// position, then the temperature and radius, as the half floats of one
+    /// word like `Instances` with `halves`, or else as two floats.
+    pub fn bind_attributes(gl: &GL, stride: usize, halves: bool) {
+        let word = std::mem::size_of::<f32>() as i32;
+        let (scalar, scalar_bytes) = if halves {
+            (GL::HALF_FLOAT, word / 2)
+        } else {
+            (GL::FLOAT, word)
+        };
+        // position, previous position, temperature, radius
+        for (location, size, kind, offset) in [
+            (0, 3, GL::FLOAT, 0),
+            (1, 3, GL::FLOAT, 3 * word),
+            (2, 1, scalar, 6 * word),
+            (3, 1, scalar, 6 * word + scalar_bytes),
+        ] {
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(
+                location,
+                size,
+                kind,
+                false,
+                stride as i32 * word,
+                offset,
+            );
+            gl.vertex_attrib_divisor(location, 1);
+        }
+    }
+
+    /// Uploads `Palette::emission_table` once, as a row of half floats
+    /// filtered linearly.
+    fn create_lut(gl: &GL) -> Result<WebGlTexture, JsValue> {
+        let table = Palette::black_body()
+            .emission_table()
+            .iter()
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 1.0])
+            .collect::<Vec<_>>();
+
+        let texture = gl.create_texture().ok_or("Failed to create texture")?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        unsafe {
+            let view = js_sys::Float32Array::view(&table);
+            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+                GL::TEXTURE_2D,
+                0,
+                GL::RGBA16F as i32,
+                (table.len() / 4) as i32,
+                1,
+                0,
+                GL::RGBA,
+                GL::FLOAT,
+                Some(&view),
+            )?;
+        }
+        for (parameter, value) in [
+            (GL::TEXTURE_MIN_FILTER, GL::LINEAR),
+            (GL::TEXTURE_MAG_FILTER, GL::LINEAR),
+            (GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE),
+            (GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameteri(GL::TEXTURE_2D, parameter, value as i32);
+        }
+        gl.bind_texture(GL::TEXTURE_2D, None);
+        Ok(texture)
+    }
+
+    /// Uploads the instances, reallocating only when they no longer fit, and
+    /// returns the bytes written.
+    pub fn upload(&mut self, gl: &GL, instances: &Instances) -> usize {
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        let capacity = grow_capacity(self.capacity, instances.len());
+        if capacity != self.capacity {
+            let bytes = capacity * INSTANCE_FLOATS * std::mem::size_of::<f32>();
+            gl.buffer_data_with_i32(GL::ARRAY_BUFFER, bytes as i32, GL::DYNAMIC_DRAW);
+            self.capacity = capacity;
+        }
+        unsafe {
+            // the view must not outlive the borrow; nothing allocates in
+            // between
+            let view = js_sys::Float32Array::view(&instances.data);
+            gl.buffer_sub_data_with_i32_and_array_buffer_view(GL::ARRAY_BUFFER, 0, &view);
+        }
+        upload_bytes(&[&instances.data], &Indices::default())
+    }
+
+    /// Draws the uploaded instances; `cvd` is column major.
+    pub fn draw(&self, gl: &GL, count: usize, mode: DrawMode, view: &View, cvd: &[f32]) {
+        gl.bind_vertex_array(Some(&self.vao));
+        self.draw_bound(gl, count, mode, view, cvd);
+    }
+
+    /// Draws `count` instances from the bound vertex array, laid out by
+    /// `bind_attributes`.
+    pub fn draw_bound(&self, gl: &GL, count: usize, mode: DrawMode, view: &View, cvd: &[f32]) {
+        let program = match mode {
+            DrawMode::Sprites { .. } => &self.sprite_program,
+            _ => &self.program,
+        };
+        gl.use_program(Some(program));
+        let location = |name| gl.get_uniform_location(program, name);
+
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.lut));
+        gl.uniform1i(location("lut").as_ref(), 0);
+        let (min, max) = Palette::black_body().range();
+        gl.uniform2f(location("lutRange").as_ref(), min, max);
+        gl.uniform_matrix4fv_with_f32_array(location("mvpMatrix").as_ref(), false, &view.mvp);
+        gl.uniform_matrix3fv_with_f32_array(location("cvdMatrix").as_ref(), false, cvd);
+
+        let (index, primitive, vertices) = match mode {
+            DrawMode::Lines => (0, GL::LINES, 2),
+            DrawMode::Sprites { scale } => {
+                gl.uniform1f(
+                    location("pixelsPerMeter").as_ref(),
+                    pixels_per_meter(view.height),
+                );
+                gl.uniform1f(location("scale").as_ref(), scale);
+                (1, GL::POINTS, 1)
+            }
+            DrawMode::Ribbons { width } => {
+                let [x, y, z] = view.eye;
+                gl.uniform3f(location("eye").as_ref(), x, y, z);
+                gl.uniform1f(location("width").as_ref(), width);
+                // the quads face the camera either way round
+                gl.disable(GL::CULL_FACE);
+                (2, GL::TRIANGLE_STRIP, 4)
+            }
+        };
+        gl.uniform1i(location("mode").as_ref(), index);
+        gl.draw_arrays_instanced(primitive, 0, vertices, count as i32);
+        gl.bind_texture(GL::TEXTURE_2D, None);
+    }
+
+    pub fn delete(&self, gl: &GL) {
+        gl.delete_program(Some(&self.program));
+        gl.delete_program(Some(&self.sprite_program));
+        gl.delete_vertex_array(Some(&self.vao));
+        gl.delete_buffer(Some(&self.buffer));
+        gl.delete_texture(Some(&self.lut));
+    }
+}
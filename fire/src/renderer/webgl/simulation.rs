@@ -0,0 +1,181 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGl2RenderingContext as GL, *};
+
+use super::instanced::InstancedPass;
+use super::View;
+use crate::fire::kernel::{Kernel, KernelSimulation, PARTICLE_FLOATS};
+use crate::renderer::DrawMode;
+use crate::shader::*;
+
+// outputs of `simulate.glsl`, in the order of `KernelParticle`
+const VARYINGS: [&str; 7] = [
+    "outPosition",
+    "outPrevious",
+    "outTemperature",
+    "outRadius",
+    "outVelocity",
+    "outAge",
+    "outLifetime",
+];
+
+/// The particle pool on the GPU, stepped by transform feedback from one
+/// buffer into the other and drawn straight from the newer one.
+pub struct SimulationPass {
+    program: WebGlProgram,
+    transform_feedback: WebGlTransformFeedback,
+    // the slots, read from one and written to the other in turn
+    buffers: [WebGlBuffer; 2],
+    // simulation input and instanced drawing, per buffer
+    simulate_vaos: [WebGlVertexArrayObject; 2],
+    draw_vaos: [WebGlVertexArrayObject; 2],
+    capacity: usize,
+    // buffer holding the latest state
+    current: usize,
+}
+
+impl SimulationPass {
+    /// Uploads the slots of `simulation`, which then live on the GPU.
+    pub fn new(gl: &GL, simulation: &KernelSimulation) -> Result<Self, JsValue> {
+        let vertex_shader = create_shader(
+            gl,
+            GL::VERTEX_SHADER,
+            include_str!("../../shader/simulate.glsl"),
+        )?;
+        let fragment_shader = create_shader(
+            gl,
+            GL::FRAGMENT_SHADER,
+            include_str!("../../shader/discard.glsl"),
+        )?;
+        let program = link_program_with_varyings(gl, &vertex_shader, &fragment_shader, &VARYINGS)?;
+        let transform_feedback = gl
+            .create_transform_feedback()
+            .ok_or("Failed to create transform feedback")?;
+
+        let data = simulation.data();
+        let create_buffer = || -> Result<WebGlBuffer, JsValue> {
+            let buffer = gl.create_buffer().ok_or("Failed to create buffer")?;
+            gl.bind_buffer(GL::ARRAY_BUFFER, Some(&buffer));
+            unsafe {
+                let view = js_sys::Float32Array::view(&data);
+                gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &view, GL::DYNAMIC_COPY);
+            }
+            Ok(buffer)
+        };
+        let buffers = [create_buffer()?, create_buffer()?];
+
+        let (simulate_a, draw_a) = Self::create_vertex_arrays(gl, &buffers[0])?;
+        let (simulate_b, draw_b) = Self::create_vertex_arrays(gl, &buffers[1])?;
+        Ok(Self {
+            program,
+            transform_feedback,
+            buffers,
+            simulate_vaos: [simulate_a, simulate_b],
+            draw_vaos: [draw_a, draw_b],
+            capacity: simulation.capacity(),
+            current: 0,
+        })
+    }
+
+    /// Vertex arrays reading `buffer` as the kernel's input and as instances.
+    fn create_vertex_arrays(
+        gl: &GL,
+        buffer: &WebGlBuffer,
+    ) -> Result<(WebGlVertexArrayObject, WebGlVertexArrayObject), JsValue> {
+        let create_vertex_array = || {
+            gl.create_vertex_array()
+                .ok_or("Failed to create vertex array object")
+        };
+        let simulate = create_vertex_array()?;
+        gl.bind_vertex_array(Some(&simulate));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+        let stride = (PARTICLE_FLOATS * std::mem::size_of::<f32>()) as i32;
+        // the fields of `KernelParticle`
+        let attributes = [(3, 0), (3, 3), (1, 6), (1, 7), (3, 8), (1, 11), (1, 12)];
+        for (location, (size, offset)) in attributes.into_iter().enumerate() {
+            gl.enable_vertex_attrib_array(location as u32);
+            gl.vertex_attrib_pointer_with_i32(
+                location as u32,
+                size,
+                GL::FLOAT,
+                false,
+                stride,
+                offset * std::mem::size_of::<f32>() as i32,
+            );
+        }
+
+        let draw = create_vertex_array()?;
+        gl.bind_vertex_array(Some(&draw));
+        InstancedPass::bind_attributes(gl, PARTICLE_FLOATS, false);
+        gl.bind_vertex_array(None);
+        gl.bind_buffer(GL::ARRAY_BUFFER, None);
+        Ok((simulate, draw))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Runs the kernel over every slot, into the other buffer.
+    pub fn step(&mut self, gl: &GL, kernel: &Kernel) {
+        gl.use_program(Some(&self.program));
+        let location = |name| gl.get_uniform_location(&self.program, name);
+        let emitter = &kernel.emitter;
+        gl.uniform1ui(location("seed").as_ref(), kernel.seed);
+        gl.uniform1ui(location("frame").as_ref(), kernel.frame);
+        gl.uniform1f(location("period").as_ref(), kernel.period);
+        gl.uniform1f(location("bounds").as_ref(), kernel.bounds);
+        gl.uniform1i(location("maxSubsteps").as_ref(), kernel.max_substeps as i32);
+        gl.uniform1f(location("burnRate").as_ref(), kernel.burn_rate);
+        let [x, y, z] = emitter.position;
+        gl.uniform3f(location("emitterPosition").as_ref(), x, y, z);
+        gl.uniform1f(location("emitterRadius").as_ref(), emitter.radius);
+        for (name, (min, max)) in [
+            ("emitterLifetime", emitter.lifetime),
+            ("emitterSpeed", emitter.speed),
+            ("emitterTemperature", emitter.temperature),
+            ("sparkRadius", emitter.spark_radius),
+        ] {
+            gl.uniform2f(location(name).as_ref(), min, max);
+        }
+
+        let next = 1 - self.current;
+        gl.bind_vertex_array(Some(&self.simulate_vaos[self.current]));
+        gl.bind_transform_feedback(GL::TRANSFORM_FEEDBACK, Some(&self.transform_feedback));
+        gl.bind_buffer_base(GL::TRANSFORM_FEEDBACK_BUFFER, 0, Some(&self.buffers[next]));
+        gl.enable(GL::RASTERIZER_DISCARD);
+        gl.begin_transform_feedback(GL::POINTS);
+        gl.draw_arrays(GL::POINTS, 0, self.capacity as i32);
+        gl.end_transform_feedback();
+        gl.disable(GL::RASTERIZER_DISCARD);
+        // a buffer bound for feedback cannot be read as attributes
+        gl.bind_buffer_base(GL::TRANSFORM_FEEDBACK_BUFFER, 0, None);
+        gl.bind_transform_feedback(GL::TRANSFORM_FEEDBACK, None);
+        gl.bind_vertex_array(None);
+        self.current = next;
+    }
+
+    /// Draws every slot of the latest state as an instance; dead ones are
+    /// clipped by the vertex shader.
+    pub fn draw(
+        &self,
+        gl: &GL,
+        instanced: &InstancedPass,
+        mode: DrawMode,
+        view: &View,
+        cvd: &[f32],
+    ) {
+        gl.bind_vertex_array(Some(&self.draw_vaos[self.current]));
+        instanced.draw_bound(gl, self.capacity, mode, view, cvd);
+    }
+
+    pub fn delete(&self, gl: &GL) {
+        gl.delete_program(Some(&self.program));
+        gl.delete_transform_feedback(Some(&self.transform_feedback));
+        for vao in self.simulate_vaos.iter().chain(&self.draw_vaos) {
+            gl.delete_vertex_array(Some(vao));
+        }
+        for buffer in self.buffers.iter() {
+            gl.delete_buffer(Some(buffer));
+        }
+    }
+}
@@ -0,0 +1,49 @@
+/// Minimal PCG32 (XSH RR) random number generator.
+///
+/// Integer only, so the same seed gives the same sequence on every target,
+/// native or wasm32.
+/// ref: https://www.pcg-random.org/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+const DEFAULT_STREAM: u64 = 1442695040888963407;
+
+impl Pcg32 {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: DEFAULT_STREAM | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    /// Uniform in [0, 1), with 24 bits of precision.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Uniform in [low, high).
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.next_f32()
+    }
+
+    /// Uniform in [low, high]; the modulo bias is negligible for small ranges.
+    pub fn gen_usize(&mut self, low: usize, high: usize) -> usize {
+        low + self.next_u32() as usize % (high - low + 1)
+    }
+}
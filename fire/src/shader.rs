@@ -29,12 +29,30 @@ pub fn link_program(
     gl: &GL,
     vertex_shader: &WebGlShader,
     fragment_shader: &WebGlShader,
+) -> Result<WebGlProgram, JsValue> {
+    link_program_with_varyings(gl, vertex_shader, fragment_shader, &[])
+}
+
+/// Links a program capturing the vertex shader outputs `varyings`,
+/// interleaved in that order, by transform feedback.
+pub fn link_program_with_varyings(
+    gl: &GL,
+    vertex_shader: &WebGlShader,
+    fragment_shader: &WebGlShader,
+    varyings: &[&str],
 ) -> Result<WebGlProgram, JsValue> {
     let program = gl
         .create_program()
         .ok_or_else(|| JsValue::from_str("Failed to create program object"))?;
     gl.attach_shader(&program, vertex_shader);
     gl.attach_shader(&program, fragment_shader);
+    if !varyings.is_empty() {
+        let names = varyings
+            .iter()
+            .map(|&name| JsValue::from_str(name))
+            .collect::<js_sys::Array>();
+        gl.transform_feedback_varyings(&program, &names, GL::INTERLEAVED_ATTRIBS);
+    }
     gl.link_program(&program);
 
     if gl
@@ -0,0 +1,66 @@
+#version 300 es
+
+precision highp float;
+
+in vec2 uv;
+out vec4 fragmentColor;
+
+uniform sampler2D scene;
+uniform sampler2D bloom;
+uniform float bloomIntensity;
+uniform float exposure;
+// 0: Reinhard, 1: ACES, 2: AgX
+uniform int toneMapping;
+// color vision deficiency simulation; identity when disabled
+uniform mat3 cvdMatrix;
+
+vec3 aces(vec3 x) {
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp(x * (a * x + b) / (x * (c * x + d) + e), 0.0, 1.0);
+}
+
+vec3 agx(vec3 x) {
+    const mat3 inset = mat3(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104);
+    const mat3 outset = mat3(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116);
+    const float minEv = -12.47393;
+    const float maxEv = 4.026069;
+
+    x = clamp(log2(inset * x), minEv, maxEv);
+    x = (x - minEv) / (maxEv - minEv);
+    // default contrast sigmoid
+    vec3 x2 = x * x;
+    vec3 x4 = x2 * x2;
+    x = 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x
+        + 0.4298 * x2 + 0.1191 * x - 0.00232;
+    return pow(max(outset * x, 0.0), vec3(2.2));
+}
+
+vec3 encodeSrgb(vec3 x) {
+    x = clamp(x, 0.0, 1.0);
+    return mix(12.92 * x, 1.055 * pow(x, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, x));
+}
+
+void main() {
+    vec3 hdr = texture(scene, uv).rgb + bloomIntensity * texture(bloom, uv).rgb;
+    hdr *= exposure;
+
+    vec3 ldr;
+    if (toneMapping == 0) {
+        ldr = hdr / (1.0 + hdr);
+    } else if (toneMapping == 1) {
+        ldr = aces(hdr);
+    } else {
+        ldr = agx(hdr);
+    }
+    fragmentColor = vec4(encodeSrgb(cvdMatrix * ldr), 1.0);
+}
@@ -0,0 +1,11 @@
+#version 300 es
+
+precision mediump float;
+
+// never runs; transform feedback passes discard the rasterizer, but a
+// program still needs a fragment shader
+out vec4 fragmentColor;
+
+void main() {
+    fragmentColor = vec4(0.0);
+}
@@ -0,0 +1,22 @@
+#version 300 es
+
+precision highp float;
+
+in vec2 uv;
+out vec4 fragmentColor;
+
+uniform sampler2D source;
+// half a texel of the source
+uniform vec2 halfTexel;
+// radiance below this does not glow; 0 after the first level
+uniform float threshold;
+
+// dual-Kawase downsampling filter
+void main() {
+    vec3 sum = texture(source, uv).rgb * 4.0;
+    sum += texture(source, uv - halfTexel).rgb;
+    sum += texture(source, uv + halfTexel).rgb;
+    sum += texture(source, uv + vec2(halfTexel.x, -halfTexel.y)).rgb;
+    sum += texture(source, uv - vec2(halfTexel.x, -halfTexel.y)).rgb;
+    fragmentColor = vec4(max(sum / 8.0 - threshold, 0.0), 1.0);
+}
@@ -5,6 +5,9 @@ precision highp float;
 in vec4 vertexColor;
 out vec4 fragmentColor;
 
+// color vision deficiency simulation; identity when disabled
+uniform mat3 cvdMatrix;
+
 void main() {
-    fragmentColor = vertexColor;
+    fragmentColor = vec4(cvdMatrix * vertexColor.rgb, vertexColor.a);
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+#version 300 es
+
+out vec2 uv;
+
+// a single triangle covering the screen, without any vertex buffer
+void main() {
+    vec2 corner = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    uv = corner;
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,59 @@
+#version 300 es
+
+// per instance
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 previous;
+layout(location = 2) in float temperature;
+// spark radius [m]
+layout(location = 3) in float size;
+
+uniform mat4 mvpMatrix;
+// emitted light from the lowest temperature up
+uniform sampler2D lut;
+// temperatures [K] of the first and last texels
+uniform vec2 lutRange;
+// 0: lines, 1: sprites, 2: ribbons
+uniform int mode;
+uniform float pixelsPerMeter;
+uniform float scale;
+uniform vec3 eye;
+uniform float width;
+
+out vec4 vertexColor;
+
+void main() {
+    // dead slots of the GPU simulation are cold; clip them away
+    if (temperature <= 0.0) {
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        gl_PointSize = 0.0;
+        vertexColor = vec4(0.0);
+        return;
+    }
+
+    // texel centers span the temperature range
+    float n = float(textureSize(lut, 0).x);
+    float x = clamp((temperature - lutRange.x) / (lutRange.y - lutRange.x), 0.0, 1.0);
+    vertexColor = vec4(texture(lut, vec2((x * (n - 1.0) + 0.5) / n, 0.5)).rgb, 1.0);
+
+    if (mode == 1) {
+        gl_Position = mvpMatrix * vec4(position, 1.0);
+        // below a pixel, dim instead of shrinking, so the sprite keeps its light
+        float diameter = 2.0 * size * scale * pixelsPerMeter / gl_Position.w;
+        float clamped = max(diameter, 1.0);
+        gl_PointSize = clamped;
+        vertexColor.rgb *= (diameter * diameter) / (clamped * clamped);
+        return;
+    }
+
+    // lines run from the previous position to the head; ribbons strip
+    // across both sides of each end
+    bool head = mode == 0 ? gl_VertexID == 1 : gl_VertexID >= 2;
+    vec3 p = head ? position : previous;
+    if (mode == 2) {
+        float side = (gl_VertexID & 1) == 0 ? -1.0 : 1.0;
+        vec3 normal = cross(position - previous, eye - p);
+        float norm = length(normal);
+        p += norm < 1.0e-12 ? vec3(0.0) : normal * (0.5 * side * width / norm);
+    }
+    gl_Position = mvpMatrix * vec4(p, 1.0);
+}
@@ -0,0 +1,26 @@
+#version 300 es
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 color;
+// direction of the segment
+layout(location = 2) in vec3 tangent;
+// -1 or 1; which edge of the ribbon the vertex is on
+layout(location = 3) in float side;
+
+uniform mat4 mvpMatrix;
+uniform vec3 eye;
+// ribbon width [m]
+uniform float width;
+
+out vec4 vertexColor;
+
+// widens the segment perpendicular to itself and to the line of sight, so
+// the quad faces the camera
+void main() {
+    vec3 normal = cross(tangent, eye - position);
+    float norm = length(normal);
+    vec3 offset = norm < 1.0e-12 ? vec3(0.0) : normal * (0.5 * side * width / norm);
+
+    vertexColor = color;
+    gl_Position = mvpMatrix * vec4(position + offset, 1.0);
+}
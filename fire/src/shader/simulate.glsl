@@ -0,0 +1,152 @@
+#version 300 es
+
+// one slot of the pool per vertex, written back by transform feedback; see
+// `fire::kernel`, which this follows line by line
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 previous;
+layout(location = 2) in float temperature;
+layout(location = 3) in float radius;
+layout(location = 4) in vec3 velocity;
+layout(location = 5) in float age;
+layout(location = 6) in float lifetime;
+
+uniform uint seed;
+uniform uint frame;
+// time between spawns of a slot [s]; 0 never spawns
+uniform float period;
+uniform float bounds;
+uniform int maxSubsteps;
+// fall of the square of the diameter [m^2/s]
+uniform float burnRate;
+uniform vec3 emitterPosition;
+uniform float emitterRadius;
+// ranges
+uniform vec2 emitterLifetime;
+uniform vec2 emitterSpeed;
+uniform vec2 emitterTemperature;
+uniform vec2 sparkRadius;
+
+out vec3 outPosition;
+out vec3 outPrevious;
+out float outTemperature;
+out float outRadius;
+out vec3 outVelocity;
+out float outAge;
+out float outLifetime;
+
+const float PI = 3.14159265;
+const float TIME_DELTA = 0.010;
+const float GRAVITY = 9.81;
+const float VISIBLE_TEMPERATURE = 798.0;
+const float AIR_TEMPERATURE = 293.0;
+const float AIR_DENSITY = 1.2;
+const float VISCOSITY = 1.5e-5;
+const float CONDUCTIVITY = 0.026;
+const float PRANDTL = 0.71;
+const float STEFAN_BOLTZMANN = 5.670374e-8;
+const float DENSITY = 1800.0;
+const float HEAT_CAPACITY = 1000.0;
+const float EMISSIVITY = 0.9;
+const float MAX_TRAVEL = 0.1;
+const float MAX_DRAG_DECAY = 0.5;
+
+// ref: https://jcgt.org/published/0009/03/02/
+uint pcgHash(uint x) {
+    uint state = x * 747796405u + 2891336453u;
+    uint word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+uint state;
+
+float random(vec2 range) {
+    state = pcgHash(state);
+    return range.x + (range.y - range.x) * float(state >> 8u) * (1.0 / 16777216.0);
+}
+
+float dragPerSpeed(float r, float speed) {
+    float area = PI * r * r;
+    float re = speed * 2.0 * r / VISCOSITY;
+    float cdSpeed = re < 1000.0
+        ? 24.0 * VISCOSITY / (2.0 * r) * (1.0 + 0.15 * pow(re, 0.687))
+        : 0.44 * speed;
+    return 0.5 * AIR_DENSITY * area * cdSpeed;
+}
+
+float radiation(float r, float t) {
+    float area = 4.0 * PI * r * r;
+    float t2 = t * t;
+    float air2 = AIR_TEMPERATURE * AIR_TEMPERATURE;
+    return EMISSIVITY * STEFAN_BOLTZMANN * area * (t2 * t2 - air2 * air2);
+}
+
+float convection(float r, float speed, float t) {
+    float re = speed * 2.0 * r / VISCOSITY;
+    float nusselt = 2.0 + 0.6 * sqrt(re) * pow(PRANDTL, 1.0 / 3.0);
+    float h = nusselt * CONDUCTIVITY / (2.0 * r);
+    return h * 4.0 * PI * r * r * (t - AIR_TEMPERATURE);
+}
+
+void main() {
+    state = pcgHash(uint(gl_VertexID) + pcgHash(frame + pcgHash(seed)));
+    vec3 p = position;
+    vec3 v = velocity;
+    vec3 last = previous;
+    float t = temperature;
+    float r = radius;
+    float a = age;
+    float life = lifetime;
+
+    if (period > 0.0 && a >= period) {
+        // the draws of `Emitter::spawn`, in its order
+        life = random(emitterLifetime);
+        float speed = random(emitterSpeed);
+        float latitude = random(vec2(0.0, 2.0 * PI));
+        float longitude = random(vec2(0.0, 2.0 * PI));
+        t = random(emitterTemperature);
+        r = random(sparkRadius);
+        vec3 direction = vec3(
+            cos(latitude) * sin(longitude),
+            cos(latitude) * cos(longitude),
+            sin(latitude));
+        p = emitterPosition + direction * emitterRadius;
+        last = p;
+        v = direction * speed;
+        a -= period;
+    }
+
+    if (t >= VISIBLE_TEMPERATURE) {
+        last = p;
+        float mass = 4.0 / 3.0 * PI * r * r * r * DENSITY;
+        float travel = length(v) * TIME_DELTA / MAX_TRAVEL;
+        float decay = dragPerSpeed(r, length(v)) / mass * TIME_DELTA / MAX_DRAG_DECAY;
+        int substeps = clamp(int(ceil(max(travel, decay))), 1, maxSubsteps);
+        float dt = TIME_DELTA / float(substeps);
+        for (int i = 0; i < substeps; i++) {
+            float k = dragPerSpeed(r, length(v)) / mass;
+            v -= (k * v + vec3(0.0, GRAVITY, 0.0)) * dt;
+            p += v * dt;
+        }
+
+        float power = -radiation(r, t) - convection(r, length(v), t);
+        r = 0.5 * sqrt(max(4.0 * r * r - burnRate * TIME_DELTA, 0.0));
+        mass = 4.0 / 3.0 * PI * r * r * r * DENSITY;
+        t = mass > 0.0
+            ? max(t + power / (mass * HEAT_CAPACITY) * TIME_DELTA, AIR_TEMPERATURE)
+            : AIR_TEMPERATURE;
+        a += TIME_DELTA;
+        if (a >= life || t < VISIBLE_TEMPERATURE || any(greaterThan(abs(p), vec3(bounds)))) {
+            t = 0.0;
+        }
+    } else {
+        a += TIME_DELTA;
+    }
+
+    outPosition = p;
+    outPrevious = last;
+    outTemperature = t;
+    outRadius = r;
+    outVelocity = v;
+    outAge = a;
+    outLifetime = life;
+}
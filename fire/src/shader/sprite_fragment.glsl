@@ -0,0 +1,20 @@
+#version 300 es
+
+precision highp float;
+
+in vec4 vertexColor;
+out vec4 fragmentColor;
+
+// color vision deficiency simulation; identity when disabled
+uniform mat3 cvdMatrix;
+
+// round, soft-edged disc
+void main() {
+    vec2 p = gl_PointCoord * 2.0 - 1.0;
+    float r2 = dot(p, p);
+    if (r2 > 1.0) {
+        discard;
+    }
+    float falloff = (1.0 - r2) * (1.0 - r2);
+    fragmentColor = vec4(cvdMatrix * vertexColor.rgb * falloff, vertexColor.a);
+}
@@ -0,0 +1,29 @@
+#version 300 es
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 color;
+// spark radius [m]; 0 draws nothing
+layout(location = 2) in float size;
+
+uniform mat4 mvpMatrix;
+// pixels spanned by a meter at unit distance
+uniform float pixelsPerMeter;
+// sprite radius over spark radius, for the glow around it
+uniform float scale;
+
+out vec4 vertexColor;
+
+void main() {
+    gl_Position = mvpMatrix * vec4(position, 1.0);
+
+    // below a pixel, dim instead of shrinking, so the sprite keeps its light
+    float diameter = 2.0 * size * scale * pixelsPerMeter / gl_Position.w;
+    float clamped = max(diameter, 1.0);
+    gl_PointSize = clamped;
+    vertexColor = vec4(color.rgb * (diameter * diameter) / (clamped * clamped), color.a);
+
+    if (size <= 0.0) {
+        // outside the view volume, so it is clipped
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+    }
+}
@@ -0,0 +1,24 @@
+#version 300 es
+
+precision highp float;
+
+in vec2 uv;
+out vec4 fragmentColor;
+
+uniform sampler2D source;
+// half a texel of the source
+uniform vec2 halfTexel;
+
+// dual-Kawase upsampling filter; blended onto the level above
+void main() {
+    vec2 h = halfTexel;
+    vec3 sum = texture(source, uv + vec2(-2.0 * h.x, 0.0)).rgb;
+    sum += texture(source, uv + vec2(-h.x, h.y)).rgb * 2.0;
+    sum += texture(source, uv + vec2(0.0, 2.0 * h.y)).rgb;
+    sum += texture(source, uv + vec2(h.x, h.y)).rgb * 2.0;
+    sum += texture(source, uv + vec2(2.0 * h.x, 0.0)).rgb;
+    sum += texture(source, uv + vec2(h.x, -h.y)).rgb * 2.0;
+    sum += texture(source, uv + vec2(0.0, -2.0 * h.y)).rgb;
+    sum += texture(source, uv + vec2(-h.x, -h.y)).rgb * 2.0;
+    fragmentColor = vec4(sum / 12.0, 1.0);
+}
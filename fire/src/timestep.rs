@@ -0,0 +1,74 @@
+/// Fixed timestep accumulator driven by real frame timestamps.
+///
+/// The display calls back at its own rate (60 Hz, 120 Hz, or throttled in a
+/// background tab); the accumulator turns the elapsed time into a whole
+/// number of simulation steps, so the animation runs at the same speed
+/// everywhere. The leftover fraction of a step is `alpha`, used to
+/// interpolate between the last two states when rendering.
+/// ref: https://gafferongames.com/post/fix_your_timestep/
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    /// simulated time per step [s]
+    pub step: f32,
+    /// cap on steps per frame, so a long pause cannot stall the page
+    pub max_substeps: u32,
+    /// simulated seconds per real second; below 1 is slow motion
+    pub time_scale: f32,
+    accumulator: f32,
+    last_timestamp: Option<f64>,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32) -> Self {
+        assert!(
+            step > 0.0,
+            "it requires; step > 0\n\
+            step must be grater than 0,\n\
+            but got {step}"
+        );
+        Self {
+            step,
+            max_substeps: 8,
+            time_scale: 1.0,
+            accumulator: 0.0,
+            last_timestamp: None,
+        }
+    }
+
+    pub fn with_max_substeps(mut self, max_substeps: u32) -> Self {
+        self.max_substeps = max_substeps;
+        self
+    }
+
+    pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// Feeds a frame timestamp [ms] and returns the number of steps to run.
+    pub fn advance(&mut self, timestamp: f64) -> u32 {
+        let elapsed = match self.last_timestamp {
+            Some(last) => ((timestamp - last) / 1000.0).max(0.0) as f32,
+            None => 0.0,
+        };
+        self.last_timestamp = Some(timestamp);
+        self.accumulator += elapsed * self.time_scale;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            if steps == self.max_substeps {
+                // drop the backlog rather than trying to catch up
+                self.accumulator %= self.step;
+                break;
+            }
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Fraction of a step left in the accumulator, in [0, 1).
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+}
@@ -0,0 +1,125 @@
+use fire::fire::branching::Branching;
+use fire::fire::emitter::Emitter;
+use fire::fire::particle::Particle;
+use fire::fire::physics::Physics;
+use fire::fire::{Fire, FireConfig};
+use fire::rng::Pcg32;
+
+const EMITTER: [f32; 3] = [0.0, 0.5, 0.0];
+
+fn momentum(particles: &[Particle]) -> [f32; 3] {
+    [0, 1, 2].map(|i| particles.iter().map(|p| p.mass * p.velocity[i]).sum())
+}
+
+fn spark() -> Particle {
+    let mut rng = Pcg32::new(3);
+    let mut particle = Emitter::new(EMITTER).with_speed(10.0, 20.0).spawn(&mut rng);
+    for _ in 0..5 {
+        particle.update(&Physics::default());
+    }
+    particle
+}
+
+#[test]
+fn split_conserves_momentum() {
+    let parent = spark();
+    let mut rng = Pcg32::new(11);
+    for count in 2..=5 {
+        let children = Branching::new(1.0).split(&parent, count, &mut rng);
+        assert_eq!(children.len(), count);
+
+        let before = momentum(std::slice::from_ref(&parent));
+        let after = momentum(&children);
+        for i in 0..3 {
+            assert!(
+                (before[i] - after[i]).abs() < 1.0e-4,
+                "before: {before:?}, after: {after:?}"
+            );
+        }
+        let mass = children.iter().map(|c| c.mass).sum::<f32>();
+        assert!((mass - parent.mass).abs() < 1.0e-6);
+    }
+}
+
+#[test]
+fn children_spread_around_the_parent() {
+    let parent = spark();
+    let speed = |v: [f32; 3]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let mut rng = Pcg32::new(5);
+    let children = Branching::new(1.0)
+        .with_spread(0.5)
+        .split(&parent, 3, &mut rng);
+    for child in &children {
+        let cos = (0..3)
+            .map(|i| child.velocity[i] * parent.velocity[i])
+            .sum::<f32>()
+            / (speed(child.velocity) * speed(parent.velocity));
+        assert!(cos > 0.0, "child flies backwards: {cos}");
+        assert_ne!(child.velocity, parent.velocity);
+        assert_eq!(child.generation, parent.generation + 1);
+    }
+}
+
+#[test]
+fn children_continue_the_parent_trail() {
+    let parent = spark();
+    let mut rng = Pcg32::new(5);
+    for child in Branching::new(1.0).split(&parent, 2, &mut rng) {
+        assert_eq!(child.position, parent.position);
+        assert_eq!(child.trail, parent.trail);
+    }
+}
+
+fn branching_fire(max_depth: u8) -> Fire {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0)
+            .with_speed(5.0, 10.0)
+            // big sparks, to stay alight for the whole test
+            .with_spark_radius(2.0e-3, 2.0e-3),
+        branching: Branching::new(20.0).with_max_depth(max_depth),
+        ..FireConfig::default()
+    });
+    fire.burst(10);
+    fire
+}
+
+#[test]
+fn depth_limit_stops_splitting() {
+    let mut fire = branching_fire(2);
+    for _ in 0..100 {
+        fire.step();
+    }
+    let particles = fire.particles();
+    assert!(particles.iter().all(|p| p.generation <= 2));
+    assert!(particles.iter().any(|p| p.generation == 2));
+    // at most 3 children per split, two generations deep
+    assert!(particles.len() <= 10 * 3 * 3);
+}
+
+#[test]
+fn split_segments_link_to_the_parent_head() {
+    let mut fire = branching_fire(3);
+    let mut previous = fire.update();
+    for _ in 0..50 {
+        let frame = fire.update();
+        let n = frame.vertex_count() / 2;
+        let m = previous.vertex_count() / 2;
+        assert_eq!(frame.segment_count(), n);
+        assert!(frame.segments.iter().all(|i| (i as usize) < 2 * n));
+
+        // every tail is the head of some segment of the previous frame
+        let heads = previous.positions[3 * m..].chunks(3).collect::<Vec<_>>();
+        for tail in frame.positions[..3 * n].chunks(3) {
+            assert!(heads.contains(&tail), "dangling tail: {tail:?}");
+        }
+        previous = frame;
+    }
+}
+
+#[test]
+#[should_panic(expected = "2 <= min <= max")]
+fn splits_have_two_children_at_least() {
+    Branching::new(1.0).with_children(0, 2);
+}
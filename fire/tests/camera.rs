@@ -0,0 +1,288 @@
+extern crate nalgebra_glm as glm;
+
+use fire::camera::controls::{Button, Controls, Gesture, Input};
+use fire::camera::{Camera, Limits, FOVY};
+use fire::frame::FrameData;
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::Renderer;
+
+const FRAME: f32 = 1.0 / 60.0;
+
+fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1.0e-5)
+}
+
+fn down(id: i32, position: [f32; 2], button: Button, time: f64) -> Input {
+    Input::Down {
+        id,
+        position,
+        button,
+        time,
+    }
+}
+
+fn to(id: i32, position: [f32; 2], time: f64) -> Input {
+    Input::Move { id, position, time }
+}
+
+// a drag from `from` by `delta` in `steps` moves 16 ms apart, released
+// `hold` ms after the last
+fn drag(
+    controls: &mut Controls,
+    camera: &mut Camera,
+    button: Button,
+    delta: [f32; 2],
+    steps: usize,
+    hold: f64,
+) {
+    let from = [0.5, 0.5];
+    controls.handle(camera, down(1, from, button, 0.0));
+    for i in 1..=steps {
+        let t = i as f32 / steps as f32;
+        let position = [from[0] + delta[0] * t, from[1] + delta[1] * t];
+        controls.handle(camera, to(1, position, 16.0 * i as f64));
+    }
+    let time = 16.0 * steps as f64 + hold;
+    controls.handle(camera, Input::Up { id: 1, time });
+}
+
+#[test]
+fn default_view_looks_down_the_z_axis() {
+    let camera = Camera::default();
+    assert!(close(camera.eye(), [0.0, 0.0, 3.0]));
+    assert!(close(camera.right(), [1.0, 0.0, 0.0]));
+    assert!(close(camera.up(), [0.0, 1.0, 0.0]));
+
+    // the fixed view it replaces
+    let eye = glm::vec3(0.0, 0.0, 3.0);
+    let view = glm::look_at(&eye, &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+    let expected = glm::perspective(1.6, FOVY, 0.1, 100.0) * view;
+    assert!((camera.mvp_matrix(1600, 1000) - expected).abs().max() < 1.0e-6);
+}
+
+#[test]
+fn orbit_keeps_the_distance_and_the_view_frame() {
+    let mut camera = Camera::default();
+    camera.orbit(0.7, 0.4);
+    let eye = camera.eye();
+    let norm = eye.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((norm - 3.0).abs() < 1.0e-5);
+
+    let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let (right, up) = (camera.right(), camera.up());
+    assert!(dot(right, up).abs() < 1.0e-6);
+    assert!(dot(right, eye).abs() < 1.0e-5);
+    assert!(dot(up, eye).abs() < 1.0e-5);
+    assert!(up[1] > 0.0);
+}
+
+#[test]
+fn moves_stay_within_the_limits() {
+    let limits = Limits::default()
+        .with_distance(1.0, 10.0)
+        .with_pitch(-0.5, 1.0)
+        .with_pan_radius(2.0);
+    let mut camera = Camera::default().with_limits(limits);
+
+    camera.orbit(0.0, 5.0);
+    assert_eq!(camera.pitch, 1.0);
+    camera.orbit(0.0, -5.0);
+    assert_eq!(camera.pitch, -0.5);
+
+    camera.zoom(100.0);
+    assert_eq!(camera.distance, 10.0);
+    camera.zoom(1.0e-3);
+    assert_eq!(camera.distance, 1.0);
+
+    camera.pan(10.0, 0.0);
+    let norm = camera.target.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((norm - 2.0).abs() < 1.0e-5);
+}
+
+#[test]
+#[should_panic(expected = "it requires; 0 < min <= max")]
+fn distance_limits_must_be_ordered() {
+    let _ = Limits::default().with_distance(5.0, 1.0);
+}
+
+#[test]
+fn dragging_orbits_and_the_right_button_pans() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    // half the height to the right turns a quarter turn to the left
+    drag(
+        &mut controls,
+        &mut camera,
+        Button::Primary,
+        [0.5, 0.0],
+        10,
+        500.0,
+    );
+    assert!((camera.yaw + 0.5 * std::f32::consts::PI).abs() < 1.0e-4);
+    assert_eq!(camera.target, [0.0; 3]);
+
+    let mut camera = Camera::default();
+    drag(
+        &mut controls,
+        &mut camera,
+        Button::Secondary,
+        [0.1, 0.0],
+        10,
+        500.0,
+    );
+    // the scene follows the pointer, so the target goes the other way
+    let expected = -0.1 * camera.view_height();
+    assert!(close(camera.target, [expected, 0.0, 0.0]));
+    assert_eq!(camera.yaw, 0.0);
+    assert_eq!(controls.gesture(), Gesture::Idle);
+}
+
+#[test]
+fn a_fling_carries_on_and_dies_down() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    drag(
+        &mut controls,
+        &mut camera,
+        Button::Primary,
+        [0.2, 0.0],
+        10,
+        0.0,
+    );
+    assert!(camera.is_moving());
+
+    let released = camera.yaw;
+    camera.update(FRAME);
+    let first = camera.yaw - released;
+    // the same way the drag went
+    assert!(first < 0.0);
+    camera.update(FRAME);
+    let second = camera.yaw - released - first;
+    assert!(second < 0.0 && second.abs() < first.abs());
+
+    for _ in 0..600 {
+        camera.update(FRAME);
+    }
+    assert!(!camera.is_moving());
+    let stopped = camera.yaw;
+    camera.update(FRAME);
+    assert_eq!(camera.yaw, stopped);
+}
+
+#[test]
+fn holding_still_before_release_does_not_fling() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    drag(
+        &mut controls,
+        &mut camera,
+        Button::Primary,
+        [0.2, 0.0],
+        10,
+        200.0,
+    );
+    assert!(!camera.is_moving());
+}
+
+#[test]
+fn grabbing_stops_a_fling() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    drag(
+        &mut controls,
+        &mut camera,
+        Button::Secondary,
+        [0.2, 0.1],
+        10,
+        0.0,
+    );
+    assert!(camera.is_moving());
+    controls.handle(&mut camera, down(2, [0.3, 0.3], Button::Primary, 400.0));
+    assert!(!camera.is_moving());
+    assert_eq!(controls.gesture(), Gesture::Orbit);
+}
+
+#[test]
+fn pinching_zooms_and_pans() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    controls.handle(&mut camera, down(1, [0.4, 0.5], Button::Primary, 0.0));
+    controls.handle(&mut camera, down(2, [0.6, 0.5], Button::Primary, 5.0));
+    assert_eq!(controls.gesture(), Gesture::Pinch);
+
+    // spread to twice the span: twice as close
+    controls.handle(&mut camera, to(1, [0.3, 0.5], 20.0));
+    controls.handle(&mut camera, to(2, [0.7, 0.5], 25.0));
+    assert!(
+        (camera.distance - 1.5).abs() < 1.0e-4,
+        "{}",
+        camera.distance
+    );
+    assert_eq!(camera.yaw, 0.0);
+
+    // both fingers up the screen: the scene goes up, the target down
+    controls.handle(&mut camera, to(1, [0.3, 0.4], 40.0));
+    controls.handle(&mut camera, to(2, [0.7, 0.4], 45.0));
+    assert!(camera.target[1] < 0.0);
+
+    // lifting one finger ends the pinch without a fling, and the other
+    // orbits from where it is
+    controls.handle(&mut camera, Input::Up { id: 2, time: 50.0 });
+    assert_eq!(controls.gesture(), Gesture::Orbit);
+    controls.handle(&mut camera, to(1, [0.3, 0.4], 60.0));
+    assert_eq!(camera.yaw, 0.0);
+    controls.handle(&mut camera, Input::Up { id: 1, time: 500.0 });
+    assert!(!camera.is_moving());
+}
+
+#[test]
+fn the_wheel_zooms_by_notches() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    controls.handle(&mut camera, Input::Wheel { notches: 1.0 });
+    assert!((camera.distance - 3.3).abs() < 1.0e-5);
+    controls.handle(&mut camera, Input::Wheel { notches: -2.0 });
+    assert!((camera.distance - 3.0 / 1.1).abs() < 1.0e-5);
+}
+
+#[test]
+fn unknown_pointers_are_ignored() {
+    let mut controls = Controls::default();
+    let mut camera = Camera::default();
+    controls.handle(&mut camera, to(7, [0.9, 0.9], 10.0));
+    controls.handle(&mut camera, Input::Up { id: 7, time: 20.0 });
+    assert_eq!(camera, Camera::default());
+    assert_eq!(controls.gesture(), Gesture::Idle);
+}
+
+#[test]
+fn renderers_draw_through_the_camera() {
+    let segment = FrameData {
+        positions: vec![-0.5, 0.0, 0.0, 0.5, 0.0, 0.0],
+        colors: [[1.0; 4]; 2].concat(),
+        segments: vec![0u16, 1].into(),
+        ..FrameData::default()
+    };
+    let lit_columns = |renderer: &SoftwareRenderer| {
+        (0..renderer.width())
+            .filter(|&x| (0..renderer.height()).any(|y| renderer.pixel(x, y)[0] > 0.5))
+            .count()
+    };
+
+    let mut renderer = SoftwareRenderer::new(64, 64);
+    renderer.render(&segment).unwrap();
+    let far = lit_columns(&renderer);
+
+    let mut camera = Camera::default();
+    camera.zoom(0.5);
+    renderer.set_camera(&camera);
+    renderer.render(&segment).unwrap();
+    let near = lit_columns(&renderer);
+    assert!(near.abs_diff(2 * far) <= 2, "{near} vs {far}");
+
+    // seen from the side, the segment points at the eye
+    camera.orbit(0.5 * std::f32::consts::PI, 0.0);
+    renderer.set_camera(&camera);
+    renderer.render(&segment).unwrap();
+    assert!(lit_columns(&renderer) <= 2);
+}
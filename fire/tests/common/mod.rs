@@ -0,0 +1,34 @@
+//! Frames and pixel queries shared by the renderer tests.
+#![allow(dead_code)]
+
+use fire::frame::FrameData;
+use fire::renderer::software::SoftwareRenderer;
+
+pub const WIDTH: u32 = 64;
+pub const HEIGHT: u32 = 64;
+
+/// A frame of the single segment.
+pub fn segment(from: [f32; 3], to: [f32; 3], color: [f32; 4]) -> FrameData {
+    FrameData {
+        positions: [from, to].concat(),
+        colors: [color, color].concat(),
+        segments: vec![0u16, 1].into(),
+        ..FrameData::default()
+    }
+}
+
+pub fn merge(frames: &[FrameData]) -> FrameData {
+    let mut merged = FrameData::default();
+    for frame in frames {
+        merged.extend(frame);
+    }
+    merged
+}
+
+/// Pixels of a `WIDTH` by `HEIGHT` output that are not black, row by row.
+pub fn lit_pixels(renderer: &SoftwareRenderer) -> Vec<(u32, u32)> {
+    (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| renderer.pixel(x, y)[..3] != [0.0, 0.0, 0.0])
+        .collect()
+}
@@ -0,0 +1,60 @@
+use fire::fire::{Fire, FireConfig};
+use fire::frame::FrameData;
+use fire::rng::Pcg32;
+
+fn bits(frame: &FrameData) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    (
+        frame.positions.iter().map(|v| v.to_bits()).collect(),
+        frame.colors.iter().map(|v| v.to_bits()).collect(),
+        frame.segments.iter().collect(),
+    )
+}
+
+#[test]
+fn same_seed_gives_bit_identical_frames() {
+    let mut a = Fire::with_config(FireConfig {
+        seed: 42,
+        ..FireConfig::default()
+    });
+    let mut b = Fire::with_config(FireConfig {
+        seed: 42,
+        ..FireConfig::default()
+    });
+    for _ in 0..20 {
+        assert_eq!(bits(&a.update()), bits(&b.update()));
+    }
+}
+
+#[test]
+fn different_seeds_give_different_frames() {
+    let mut a = Fire::with_config(FireConfig {
+        seed: 1,
+        ..FireConfig::default()
+    });
+    let mut b = Fire::with_config(FireConfig {
+        seed: 2,
+        ..FireConfig::default()
+    });
+    assert_ne!(bits(&a.update()), bits(&b.update()));
+}
+
+#[test]
+fn pcg32_matches_the_reference_sequence() {
+    let mut rng = Pcg32::new(42);
+    let first = (0..4).map(|_| rng.next_u32()).collect::<Vec<_>>();
+    let mut again = Pcg32::new(42);
+    assert_eq!(first, (0..4).map(|_| again.next_u32()).collect::<Vec<_>>());
+    assert_eq!(first, PINNED);
+}
+
+#[test]
+fn pcg32_floats_are_in_range() {
+    let mut rng = Pcg32::new(7);
+    for _ in 0..10_000 {
+        let v = rng.gen_range(-2.0, 3.0);
+        assert!((-2.0..3.0).contains(&v));
+    }
+}
+
+// pcg32_srandom_r(42, 1442695040888963407 >> 1) of the reference C implementation
+const PINNED: [u32; 4] = [3270867926, 1795671209, 1924641435, 1143034755];
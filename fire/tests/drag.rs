@@ -0,0 +1,106 @@
+use fire::fire::air;
+use fire::fire::particle::Particle;
+use fire::fire::physics::Physics;
+use fire::fire::thermal::{self, Oxidation};
+use fire::fire::{FireConfig, TIME_DELTA};
+use std::f32::consts;
+
+const GRAVITY: f32 = 9.81;
+
+// an inert drop, which keeps its size
+fn drop(radius: f32, seconds: f32) -> f32 {
+    let physics = Physics::default().with_burn_rate(0.0);
+    let mut particle = Particle::new([0.0; 3], [0.0; 3], 1400.0, 60.0).with_radius(radius);
+    for _ in 0..(seconds / TIME_DELTA) as usize {
+        particle.update(&physics);
+    }
+    particle.speed()
+}
+
+#[test]
+fn big_droplets_reach_newton_terminal_velocity() {
+    let radius = 5.0e-3;
+    let mass = thermal::mass(radius, thermal::DENSITY);
+    let area = consts::PI * radius * radius;
+    let expected = (2.0 * mass * GRAVITY / (air::DENSITY * 0.44 * area)).sqrt();
+    assert!(air::reynolds(radius, expected) > 1000.0);
+
+    let speed = drop(radius, 30.0);
+    assert!(
+        (speed - expected).abs() < 1.0e-3 * expected,
+        "expected: {expected}, got: {speed}"
+    );
+}
+
+#[test]
+fn dust_reaches_stokes_terminal_velocity() {
+    let radius = 5.0e-6;
+    let mu = air::DENSITY * air::VISCOSITY;
+    let expected = 2.0 / 9.0 * thermal::DENSITY * GRAVITY * radius * radius / mu;
+    assert!(air::reynolds(radius, expected) < 0.01);
+
+    let speed = drop(radius, 1.0);
+    // Schiller–Naumann adds 0.15 Re^0.687 to Stokes drag
+    assert!(
+        (speed - expected).abs() < 1.0e-2 * expected,
+        "expected: {expected}, got: {speed}"
+    );
+}
+
+#[test]
+fn drag_coefficient_levels_off() {
+    assert!((air::drag_coefficient(0.1) - 24.0 / 0.1).abs() < 0.05 * 240.0);
+    assert_eq!(air::drag_coefficient(1.0e4), 0.44);
+    // continuous where the correlation hands over
+    let below = air::drag_coefficient(999.9);
+    assert!((below - 0.44).abs() < 0.01, "got {below}");
+}
+
+#[test]
+fn small_sparks_slow_down_faster() {
+    let launch = |radius: f32| {
+        let mut particle =
+            Particle::new([0.0; 3], [30.0, 0.0, 0.0], 1400.0, 60.0).with_radius(radius);
+        for _ in 0..10 {
+            particle.update(&Physics::default());
+        }
+        particle.velocity[0]
+    };
+    let (small, big) = (launch(0.2e-3), launch(1.0e-3));
+    assert!(small < big, "small: {small}, big: {big}");
+    assert!(big < 30.0);
+}
+
+#[test]
+fn burning_sparks_shrink() {
+    let physics = Physics::default().with_oxidation(Oxidation {
+        delay: 0.0,
+        ..Oxidation::default()
+    });
+    let mut particle = Particle::new([0.0; 3], [0.0; 3], 1400.0, 60.0).with_radius(1.0e-3);
+    let (mass, radius) = (particle.mass, particle.radius);
+    for _ in 0..100 {
+        particle.update(&physics);
+    }
+    assert!(particle.radius < radius);
+    assert!((particle.mass + particle.burnt - mass).abs() < 1.0e-6 * mass);
+    let expected = thermal::radius(particle.mass, particle.density);
+    assert!((particle.radius - expected).abs() < 1.0e-9);
+}
+
+#[test]
+fn sparks_shrink_as_they_burn() {
+    let config = FireConfig::default();
+    let mut particle = Particle::new([0.0; 3], [5.0, 5.0, 0.0], 1400.0, 2.0).with_radius(0.5e-3);
+    let mut radii = vec![particle.radius];
+    while particle.age < particle.lifetime {
+        particle.update(&config.physics);
+        radii.push(particle.radius);
+    }
+    assert!(radii.windows(2).all(|w| w[1] < w[0]));
+    // d² falls linearly
+    let d2 = |r: f32| 4.0 * r * r;
+    let expected = d2(radii[0]) - config.physics.burn_rate * particle.age;
+    assert!((d2(particle.radius) - expected).abs() < 1.0e-3 * expected);
+    assert!((particle.mass - thermal::mass(particle.radius, particle.density)).abs() < 1.0e-12);
+}
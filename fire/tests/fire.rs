@@ -0,0 +1,224 @@
+use fire::fire::emitter::Emitter;
+use fire::fire::{Fire, FireConfig};
+use fire::frame::{FrameData, Indices};
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::Renderer;
+
+const EMITTER: [f32; 3] = [0.0, 0.5, 0.0];
+
+fn mean_height(frame: &FrameData) -> f32 {
+    let n = frame.vertex_count() / 2;
+    let current = &frame.positions[3 * n..];
+    current.chunks(3).map(|p| p[1]).sum::<f32>() / n as f32
+}
+
+#[test]
+fn frame_links_previous_to_current_positions() {
+    let mut fire = Fire::new();
+    let frame = fire.update();
+
+    let n = frame.vertex_count() / 2;
+    assert_eq!(frame.positions.len(), 3 * 2 * n);
+    assert_eq!(frame.colors.len(), 4 * 2 * n);
+    assert_eq!(frame.segment_count(), n);
+    let segments = frame.segments.iter().collect::<Vec<_>>();
+    for (i, segment) in segments.chunks(2).enumerate() {
+        assert_eq!(segment, [i as u32, (i + n) as u32]);
+    }
+}
+
+#[test]
+fn sparks_start_at_the_emitter() {
+    let mut fire = Fire::new();
+    let frame = fire.update();
+
+    let n = frame.vertex_count() / 2;
+    for previous in frame.positions[..3 * n].chunks(3) {
+        assert_eq!(previous, EMITTER);
+    }
+    // a single 10 ms step at no more than 50 m/s
+    for (previous, current) in frame.positions[..3 * n]
+        .chunks(3)
+        .zip(frame.positions[3 * n..].chunks(3))
+    {
+        let length = (0..3)
+            .map(|i| (current[i] - previous[i]).powi(2))
+            .sum::<f32>()
+            .sqrt();
+        assert!(length <= 0.5 + 1.0e-3, "segment too long: {length}");
+    }
+}
+
+/// A single burst of long lived sparks, without continuous emission.
+fn burst(count: usize) -> Fire {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0)
+            // big sparks cool slowly
+            .with_spark_radius(2.0e-3, 2.0e-3),
+        ..FireConfig::default()
+    });
+    fire.burst(count);
+    fire
+}
+
+#[test]
+fn gravity_pulls_sparks_down() {
+    let mut fire = burst(100);
+    let mut frame = fire.update();
+    for _ in 0..150 {
+        frame = fire.update();
+    }
+    assert!(mean_height(&frame) < EMITTER[1] - 5.0);
+}
+
+#[test]
+fn colors_are_opaque_and_non_negative() {
+    let mut fire = Fire::new();
+    for _ in 0..10 {
+        let frame = fire.update();
+        for color in frame.colors.chunks(4) {
+            assert!(color[..3].iter().all(|&c| c >= 0.0), "got {color:?}");
+            assert_eq!(color[3], 1.0);
+        }
+    }
+}
+
+#[test]
+fn emitter_spawns_at_its_rate() {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(300.0)
+            .with_lifetime(60.0, 60.0),
+        ..FireConfig::default()
+    });
+    for _ in 0..10 {
+        fire.step();
+    }
+    // 0.1 s at 300 sparks per second, minus any that left the bounds
+    assert!((25..=30).contains(&fire.particle_count()));
+}
+
+#[test]
+fn sparks_burn_out_after_their_lifetime() {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER).with_rate(0.0).with_lifetime(0.1, 0.2),
+        ..FireConfig::default()
+    });
+    fire.burst(50);
+    for _ in 0..10 {
+        fire.step();
+    }
+    assert!(fire.particle_count() > 0);
+    for _ in 0..11 {
+        fire.step();
+    }
+    assert_eq!(fire.particle_count(), 0);
+}
+
+#[test]
+fn sparks_leaving_the_bounds_despawn() {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0),
+        bounds: 1.0,
+        ..FireConfig::default()
+    });
+    fire.burst(100);
+    // nothing stays within a metre for 10 s under gravity
+    for _ in 0..1000 {
+        fire.step();
+    }
+    assert_eq!(fire.particle_count(), 0);
+}
+
+#[test]
+fn pool_memory_stays_flat() {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER).with_rate(5000.0),
+        max_particles: 500,
+        ..FireConfig::default()
+    });
+    assert_eq!(fire.capacity(), 500);
+    fire.step();
+    // the pool is never reallocated
+    let pool = fire.particles().as_ptr();
+    let mut peak = 0;
+    for _ in 0..300 {
+        fire.step();
+        assert!(fire.particle_count() <= 500);
+        assert_eq!(fire.particles().as_ptr(), pool);
+        peak = peak.max(fire.particle_count());
+    }
+    assert_eq!(peak, 500);
+}
+
+#[test]
+fn small_frames_use_16_bit_indices() {
+    let mut fire = Fire::new();
+    let frame = fire.update();
+    assert!(matches!(frame.segments, Indices::U16(_)));
+}
+
+#[test]
+fn a_hundred_thousand_sparks_make_a_valid_frame() {
+    let count = 100_000;
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0),
+        max_particles: count,
+        ..FireConfig::default()
+    });
+    fire.burst(count);
+    assert_eq!(fire.particle_count(), count);
+    let frame = fire.frame(1.0);
+
+    assert!(matches!(frame.segments, Indices::U32(_)));
+    assert_eq!(frame.vertex_count(), 2 * count);
+    assert_eq!(frame.colors.len(), 4 * 2 * count);
+    assert_eq!(frame.segment_count(), count);
+    let segments = frame.segments.iter().collect::<Vec<_>>();
+    for (i, segment) in segments.chunks(2).enumerate() {
+        assert_eq!(segment, [i as u32, (i + count) as u32]);
+    }
+
+    let mut renderer = SoftwareRenderer::new(32, 32);
+    assert_eq!(renderer.render(&frame), Ok(()));
+}
+
+#[test]
+fn indices_widen_past_16_bits() {
+    let mut indices = Indices::default();
+    indices.push(1);
+    indices.push(70_000);
+    assert_eq!(indices, Indices::U32(vec![1, 70_000]));
+
+    // 0xffff restarts primitives in WebGL2
+    let mut indices = Indices::default();
+    indices.push(65_535);
+    assert_eq!(indices, Indices::U32(vec![65_535]));
+}
+
+#[test]
+fn frames_reaching_the_restart_index_use_32_bits() {
+    // 32768 sparks make 65536 vertices, the last of them 0xffff
+    let count = 32_768;
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0),
+        max_particles: count,
+        ..FireConfig::default()
+    });
+    fire.burst(count);
+    let frame = fire.frame(1.0);
+    assert_eq!(frame.vertex_count(), 65_536);
+    assert!(matches!(frame.segments, Indices::U32(_)));
+    assert_eq!(frame.segments.iter().max(), Some(65_535));
+
+    let last = Indices::with_capacity(2, 65_535);
+    assert!(matches!(last, Indices::U16(_)));
+}
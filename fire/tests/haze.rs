@@ -0,0 +1,64 @@
+use black_body::atmosphere::Atmosphere;
+use black_body::BlackBody;
+use fire::frame::{Disc, FrameData};
+use fire::haze::Haze;
+
+fn hazy() -> Haze {
+    Haze::new(&Atmosphere::new().with_visibility(5_000.0))
+}
+
+#[test]
+fn far_sparks_are_redder_and_dimmer() {
+    let eye = [0.0, 0.0, 3.0];
+    let mut frame = FrameData {
+        // a spark at the eye and one 2 km away
+        positions: vec![0.0, 0.0, 3.0, 0.0, 0.0, -1997.0],
+        colors: vec![1.0; 8],
+        ..FrameData::default()
+    };
+    frame.attenuate(&hazy(), eye);
+
+    let (near, far) = (&frame.colors[..4], &frame.colors[4..]);
+    assert_eq!(near, &[1.0; 4]);
+    assert!(far[0] < 1.0, "{far:?}");
+    assert!(far[2] < far[1] && far[1] < far[0], "{far:?}");
+    // alpha is coverage, not light
+    assert_eq!(far[3], 1.0);
+}
+
+#[test]
+fn discs_are_tinted_like_vertices() {
+    let mut frame = FrameData {
+        positions: vec![0.0, 0.0, -500.0],
+        colors: vec![1.0; 4],
+        discs: vec![Disc {
+            center: [0.0, 0.0, -500.0],
+            color: [1.0; 4],
+            radius: 1.0,
+        }],
+        ..FrameData::default()
+    };
+    frame.attenuate(&hazy(), [0.0; 3]);
+    assert_eq!(&frame.discs[0].color[..], &frame.colors[..]);
+}
+
+#[test]
+fn haze_matches_the_spectral_transmission() {
+    let air = Atmosphere::new().with_visibility(5_000.0);
+    let haze = Haze::new(&air);
+    assert_eq!(haze.transmittance(0.0), [1.0; 3]);
+
+    // fitted at 2000 K, it holds across the temperatures of sparks
+    for kelvin in [1500.0, 2000.0, 2500.0, 3000.0] {
+        let body = BlackBody::new(kelvin);
+        let (near, far) = (body.color_for_eye(), body.color_through(&air, 1_000.0));
+        let t = haze.transmittance(1_000.0);
+        for c in 0..3 {
+            let expected = (far[c] / near[c]) as f32;
+            assert!(
+                (t[c] - expected).abs() < 0.03 * expected,
+                "{kelvin} K: {t:?} vs {expected}"
+            );
+        }
+    }
+}
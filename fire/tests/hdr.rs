@@ -0,0 +1,122 @@
+mod common;
+
+use common::{segment, HEIGHT, WIDTH};
+use fire::renderer::hdr::{encode_srgb, Bloom, Hdr, ToneMapping};
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::Renderer;
+
+const OPERATORS: [ToneMapping; 3] = [ToneMapping::Reinhard, ToneMapping::Aces, ToneMapping::Agx];
+
+fn gray(tone_mapping: ToneMapping, x: f32) -> f32 {
+    tone_mapping.apply([x; 3])[1]
+}
+
+#[test]
+fn tone_mapping_is_monotonic_and_bounded() {
+    for operator in OPERATORS {
+        assert!(gray(operator, 0.0) < 1.0e-3, "{operator}");
+        let mut previous = gray(operator, 0.0);
+        for i in 1..=1000 {
+            let y = gray(operator, i as f32 * 0.05);
+            assert!(y >= previous - 1.0e-6, "{operator} falls at {i}");
+            assert!(y <= 1.0 + 1.0e-3, "{operator} exceeds 1 at {i}: {y}");
+            previous = y;
+        }
+        assert!(previous > 0.9, "{operator} saturates at {previous}");
+    }
+}
+
+#[test]
+fn tone_mapping_matches_the_reference_curves() {
+    assert_eq!(gray(ToneMapping::Reinhard, 1.0), 0.5);
+    assert_eq!(gray(ToneMapping::Reinhard, 3.0), 0.75);
+    // Narkowicz's ACES fit maps 1 to 0.80
+    assert!((gray(ToneMapping::Aces, 1.0) - 0.8038).abs() < 1.0e-3);
+    // AgX keeps middle gray near the middle
+    let agx = gray(ToneMapping::Agx, 0.18);
+    assert!((0.1..0.3).contains(&agx), "got {agx}");
+}
+
+#[test]
+fn tone_mapping_names_round_trip() {
+    for operator in OPERATORS {
+        assert_eq!(operator.to_string().parse::<ToneMapping>(), Ok(operator));
+    }
+    assert!("filmic".parse::<ToneMapping>().is_err());
+}
+
+#[test]
+fn srgb_encoding_matches_its_definition() {
+    assert_eq!(encode_srgb(0.0), 0.0);
+    assert!((encode_srgb(1.0) - 1.0).abs() < 1.0e-6);
+    assert!((encode_srgb(0.18) - 0.4614).abs() < 1.0e-3);
+    assert_eq!(encode_srgb(2.0), encode_srgb(1.0));
+}
+
+#[test]
+fn bloom_spreads_a_bright_spot_symmetrically() {
+    let (width, height) = (32, 32);
+    let mut radiance = vec![[0.0; 3]; (width * height) as usize];
+    // a 2 x 2 spot at the center, which the mirrored image keeps
+    for (x, y) in [(15, 15), (16, 15), (15, 16), (16, 16)] {
+        radiance[(y * width + x) as usize] = [100.0; 3];
+    }
+    let glow = Bloom::default().apply(&radiance, width, height);
+
+    let at = |x: u32, y: u32| glow[(y * width + x) as usize][0];
+    assert!(at(16, 16) > 0.0);
+    assert!((at(15, 16) - at(16, 16)).abs() < 1.0e-4);
+    assert!((at(13, 16) - at(18, 16)).abs() < 1.0e-4);
+    assert!((at(16, 13) - at(16, 18)).abs() < 1.0e-4);
+    assert!(at(12, 16) > 0.0, "the glow reaches beyond the pixel");
+    assert!(at(16, 16) > at(10, 16) && at(10, 16) > at(0, 16));
+}
+
+#[test]
+fn radiance_below_the_threshold_does_not_glow() {
+    let radiance = vec![[0.9; 3]; (WIDTH * HEIGHT) as usize];
+    let glow = Bloom::default()
+        .with_threshold(1.0)
+        .apply(&radiance, WIDTH, HEIGHT);
+    assert!(glow.iter().all(|g| *g == [0.0; 3]));
+
+    let off = Bloom::default().with_intensity(0.0);
+    assert!(!off.is_enabled());
+}
+
+#[test]
+fn overlapping_sparks_add_instead_of_occluding() {
+    let red = segment([-3.0, 0.0, 0.0], [3.0, 0.0, 0.0], [0.6, 0.1, 0.0, 1.0]);
+    let near = segment([0.0, -1.0, 1.0], [0.0, 1.0, 1.0], [0.6, 0.1, 0.0, 1.0]);
+    let mut frame = red.clone();
+    frame.extend(&near);
+
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_hdr(Some(Hdr::default()));
+    renderer.render(&frame).unwrap();
+
+    let center = renderer.radiance(WIDTH / 2, HEIGHT / 2);
+    assert!((center[0] - 1.2).abs() < 1.0e-5, "got {center:?}");
+    let side = renderer.radiance(WIDTH / 4, HEIGHT / 2);
+    assert!((side[0] - 0.6).abs() < 1.0e-5, "got {side:?}");
+    assert!(renderer.pixel(WIDTH / 2, HEIGHT / 2)[0] > renderer.pixel(WIDTH / 4, HEIGHT / 2)[0]);
+}
+
+#[test]
+fn output_is_the_tone_mapped_radiance() {
+    let frame = segment([-3.0, 0.0, 0.0], [3.0, 0.0, 0.0], [4.0, 1.0, 0.25, 1.0]);
+    let hdr = Hdr::default()
+        .with_tone_mapping(ToneMapping::Reinhard)
+        .with_exposure(0.5)
+        .with_bloom(Bloom::default().with_intensity(0.0));
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_hdr(Some(hdr));
+    renderer.render(&frame).unwrap();
+
+    let pixel = renderer.pixel(WIDTH / 2, HEIGHT / 2);
+    for (c, x) in [2.0_f32, 0.5, 0.125].into_iter().enumerate() {
+        let expected = encode_srgb(x / (1.0 + x));
+        assert!((pixel[c] - expected).abs() < 1.0e-5, "got {pixel:?}");
+    }
+    assert_eq!(renderer.pixel(WIDTH / 2, 0), [0.0, 0.0, 0.0, 1.0]);
+}
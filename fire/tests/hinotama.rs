@@ -0,0 +1,146 @@
+use fire::camera::Camera;
+use fire::fire::emitter::Emitter;
+use fire::fire::hinotama::Hinotama;
+use fire::fire::{Fire, FireConfig, TIME_DELTA};
+use fire::palette::Palette;
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::{DrawMode, Renderer};
+use fire::rng::Pcg32;
+
+const ANCHOR: [f32; 3] = [0.0, 0.5, 0.0];
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f32>().sqrt()
+}
+
+fn settle(hinotama: &mut Hinotama, seconds: f32) {
+    for _ in 0..(seconds / TIME_DELTA) as usize {
+        hinotama.step(TIME_DELTA);
+    }
+}
+
+#[test]
+fn hangs_below_the_tip_at_rest() {
+    let mut hinotama = Hinotama::new(ANCHOR).with_mass(2.0e-5, 0.0);
+    settle(&mut hinotama, 5.0);
+
+    assert!(hinotama.is_attached());
+    // the spring holds the weight
+    let weight = hinotama.mass * 9.81;
+    assert!((hinotama.tension() - weight).abs() < 1.0e-2 * weight);
+    assert!(hinotama.position[1] < ANCHOR[1]);
+    assert!(hinotama.velocity.iter().all(|v| v.abs() < 1.0e-3));
+}
+
+#[test]
+fn grows_as_it_burns() {
+    let mut hinotama = Hinotama::new(ANCHOR);
+    let (mass, radius) = (hinotama.mass, hinotama.radius());
+    settle(&mut hinotama, 10.0);
+    assert!((hinotama.mass - (mass + 10.0 * hinotama.growth)).abs() < 1.0e-6);
+    assert!(hinotama.radius() > radius);
+}
+
+#[test]
+fn wobble_dies_down_after_a_gentle_shake() {
+    let mut hinotama = Hinotama::new(ANCHOR).with_mass(2.0e-5, 0.0);
+    settle(&mut hinotama, 5.0);
+    let rest = hinotama.position;
+
+    hinotama.shake([1.0e-5, 0.0, 0.0]);
+    settle(&mut hinotama, 0.05);
+    assert!(distance(hinotama.position, rest) > 1.0e-3);
+    settle(&mut hinotama, 5.0);
+    assert!(hinotama.is_attached());
+    assert!(distance(hinotama.position, rest) < 1.0e-4);
+}
+
+#[test]
+fn drops_when_shaken_hard() {
+    let mut hinotama = Hinotama::new(ANCHOR);
+    settle(&mut hinotama, 1.0);
+    hinotama.shake([0.0, -2.0e-4, 0.0]);
+    settle(&mut hinotama, 0.5);
+    assert!(!hinotama.is_attached());
+
+    // falls freely from then on
+    let velocity = hinotama.velocity[1];
+    settle(&mut hinotama, 0.1);
+    assert!((hinotama.velocity[1] - (velocity - 9.81 * 0.1)).abs() < 1.0e-3);
+}
+
+#[test]
+fn sparks_spawn_on_the_surface() {
+    let emitter = Emitter::new(ANCHOR).with_radius(0.01);
+    let mut rng = Pcg32::new(9);
+    for _ in 0..100 {
+        let spark = emitter.spawn(&mut rng);
+        assert!((distance(spark.position, ANCHOR) - 0.01).abs() < 1.0e-6);
+    }
+}
+
+#[test]
+fn fire_emits_from_the_ball_and_draws_it() {
+    let mut fire = Fire::with_config(FireConfig {
+        hinotama: Some(Hinotama::new(ANCHOR).with_temperature(1400.0)),
+        ..FireConfig::default()
+    });
+    for _ in 0..20 {
+        fire.step();
+    }
+    let hinotama = fire.hinotama().unwrap();
+    let frame = fire.frame(1.0);
+
+    // one filled disc over the sparks, adding no vertices
+    assert_eq!(frame.vertex_count(), 2 * fire.particle_count());
+    assert_eq!(frame.discs.len(), 1);
+    let disc = frame.discs[0];
+    assert_eq!(disc.center, hinotama.position);
+    assert_eq!(disc.radius, hinotama.radius());
+    // shaded from the palette like the sparks
+    let palette = Palette::black_body();
+    let emission = palette.emission(1400.0);
+    for (a, b) in disc.color.iter().zip(emission) {
+        assert!((a - b).abs() < 1.0e-6);
+    }
+}
+
+#[test]
+fn the_ball_is_drawn_as_a_filled_glowing_disc() {
+    let hinotama = Hinotama::new(ANCHOR).with_mass(0.05, 0.0);
+    let mut camera = Camera::default();
+    camera.target = hinotama.position;
+    camera.zoom(0.5 / camera.distance);
+    let mut renderer = SoftwareRenderer::new(400, 400);
+    renderer.set_camera(&camera);
+    // whatever the mode
+    for mode in [DrawMode::Lines, DrawMode::ribbons()] {
+        renderer.set_mode(mode);
+        renderer.render(&hinotama.frame(1.0)).unwrap();
+
+        // about 7.5 px across at 400 px/m
+        let radius = hinotama.radius() * 400.0;
+        let red = |dx: f32| renderer.pixel((200.0 + dx) as u32, 200)[0];
+        let rows = (0..400)
+            .filter(|&y| (0..400).any(|x| renderer.pixel(x, y)[0] > 0.0))
+            .count() as f32;
+        assert!((rows - 2.0 * radius).abs() <= 2.0, "{rows} rows");
+        // filled, and fading out from the center
+        let samples = [0.0, 0.25, 0.5, 0.75].map(|r| red(r * radius));
+        assert!(samples.windows(2).all(|w| w[0] > w[1]), "{samples:?}");
+        assert!(samples[3] > 0.0);
+    }
+}
+
+#[test]
+fn a_dropped_ball_stops_the_sparks() {
+    let mut fire = Fire::with_config(FireConfig {
+        hinotama: Some(Hinotama::new(ANCHOR).with_max_tension(0.0)),
+        ..FireConfig::default()
+    });
+    for _ in 0..300 {
+        fire.step();
+    }
+    assert!(!fire.hinotama().unwrap().is_attached());
+    assert_eq!(fire.particle_count(), 0);
+}
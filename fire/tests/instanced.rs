@@ -0,0 +1,148 @@
+use fire::fire::trail::TrailStyle;
+use fire::fire::{Fire, FireConfig};
+use fire::frame::{pack_halves, unpack_halves, Instances, INSTANCE_FLOATS};
+use fire::palette::Palette;
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::{ByteCounter, Renderer};
+
+fn burning(trail: TrailStyle) -> Fire {
+    let mut fire = Fire::with_config(FireConfig {
+        trail,
+        ..FireConfig::default()
+    });
+    fire.burst(500);
+    for _ in 0..5 {
+        fire.step();
+    }
+    fire
+}
+
+#[test]
+fn instances_expand_into_head_segments() {
+    let mut instances = Instances::default();
+    instances.push([1.0, 2.0, 3.0], [0.0, 0.0, 0.0], 1500.0, 1.0e-3);
+    instances.push([0.0, 1.0, 0.0], [0.0, 0.5, 0.0], 1000.0, 2.0e-3);
+    assert_eq!(instances.len(), 2);
+
+    let frame = instances.frame();
+    assert_eq!(frame.vertex_count(), 4);
+    assert_eq!(frame.segments.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    assert_eq!(&frame.positions[..6], &[0.0, 0.0, 0.0, 1.0, 2.0, 3.0]);
+    // only the head carries a sprite, its radius to half float precision
+    assert_eq!(frame.sizes[0], 0.0);
+    assert!((frame.sizes[1] - 1.0e-3).abs() < 1.0e-6);
+    assert!((frame.sizes[3] - 2.0e-3).abs() < 1.0e-6);
+    let emission = Palette::black_body().emission(1000.0);
+    assert_eq!(&frame.colors[8..11], &emission);
+}
+
+#[test]
+fn instances_follow_the_sparks() {
+    let fire = burning(TrailStyle::default());
+    let instances = fire.instances(1.0);
+    assert_eq!(instances.len(), fire.particle_count());
+    let sparks = instances.data.chunks_exact(INSTANCE_FLOATS);
+    for (instance, p) in sparks.zip(fire.particles()) {
+        for (x, expected) in instance[0..3].iter().zip(p.position) {
+            assert!((x - expected).abs() < 1.0e-6);
+        }
+        // half floats keep 11 significant bits
+        let [temperature, radius] = unpack_halves(instance[6]);
+        assert!((temperature - p.temperature).abs() <= p.temperature / 2048.0);
+        assert!((radius - p.radius).abs() <= p.radius / 2048.0);
+    }
+}
+
+#[test]
+fn instancing_halves_the_upload() {
+    for trail in [TrailStyle::default(), TrailStyle::streaks(16)] {
+        let instances = burning(trail).instances(1.0);
+        // the same head segments, as lines of positions, colors and indices
+        let mut lines = SoftwareRenderer::new(64, 64);
+        lines.render(&instances.frame()).unwrap();
+        let mut instanced = SoftwareRenderer::new(64, 64);
+        instanced
+            .render_instanced(&instances, &Default::default())
+            .unwrap();
+
+        let (lines, instanced) = (lines.bytes().frame, instanced.bytes().frame);
+        assert_eq!(instanced, 28 * instances.len());
+        assert!(2 * instanced <= lines, "{instanced} vs {lines} bytes");
+    }
+}
+
+#[test]
+fn halves_round_to_nearest_even() {
+    let round_trip = |x: f32| unpack_halves(pack_halves(x, x));
+    for exact in [
+        0.0,
+        1.0,
+        -2.5,
+        1500.0,
+        65504.0,
+        6.103_515_6e-5,
+        5.960_464_5e-8,
+    ] {
+        assert_eq!(round_trip(exact), [exact; 2]);
+    }
+    // 2049 lies halfway between 2048 and 2050, and rounds to the even one
+    assert_eq!(round_trip(2049.0), [2048.0; 2]);
+    assert_eq!(round_trip(2051.0), [2052.0; 2]);
+    assert_eq!(round_trip(1.0e6), [f32::INFINITY; 2]);
+    assert_eq!(round_trip(1.0e-9), [0.0; 2]);
+    assert_eq!(unpack_halves(pack_halves(1.0, 2.0)), [1.0, 2.0]);
+}
+
+#[test]
+fn emission_table_matches_the_palette() {
+    let palette = Palette::black_body();
+    let table = palette.emission_table();
+    assert_eq!(table.len(), palette.colors().len());
+
+    let (min, max) = palette.range();
+    let step = (max - min) / (table.len() - 1) as f32;
+    for (i, entry) in table.iter().enumerate().step_by(37) {
+        assert_eq!(palette.emission(min + i as f32 * step), *entry);
+    }
+    for temperature in [900.0, 1234.5, 1800.0, 2600.0] {
+        let emission = palette.emission(temperature);
+        let color = palette.color(temperature);
+        let brightness = palette.brightness(temperature);
+        for c in 0..3 {
+            let expected = color[c] * brightness;
+            assert!((emission[c] - expected).abs() <= 0.01 * expected.max(0.01));
+        }
+    }
+}
+
+#[test]
+fn software_instancing_draws_the_expanded_sparks() {
+    let fire = burning(TrailStyle::default());
+    let instances = fire.instances(1.0);
+
+    let mut instanced = SoftwareRenderer::new(64, 64);
+    instanced
+        .render_instanced(&instances, &Default::default())
+        .unwrap();
+    let mut expanded = SoftwareRenderer::new(64, 64);
+    expanded.render(&instances.frame()).unwrap();
+    assert_eq!(instanced.to_rgba8(), expanded.to_rgba8());
+    assert!(instanced.to_rgba8().chunks(4).any(|p| p[..3] != [0, 0, 0]));
+}
+
+#[test]
+fn byte_counter_resets_every_frame() {
+    let mut counter = ByteCounter::default();
+    counter.add(100);
+    counter.add(28);
+    assert_eq!(counter.frame, 128);
+    counter.begin_frame();
+    counter.add(32);
+    assert_eq!(
+        counter,
+        ByteCounter {
+            frame: 32,
+            total: 160
+        }
+    );
+}
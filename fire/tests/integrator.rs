@@ -0,0 +1,92 @@
+use fire::fire::integrator::{Integrator, Rk4, SemiImplicitEuler, VelocityVerlet};
+use fire::fire::particle::Particle;
+use fire::fire::physics::Physics;
+use fire::fire::{Fire, FireConfig};
+
+const GRAVITY: [f32; 3] = [0.0, -9.81, 0.0];
+const DRAG: f32 = 2.0; // [1/s]
+const X0: [f32; 3] = [0.0, 0.5, 0.0];
+const V0: [f32; 3] = [8.0, 12.0, -3.0];
+const DURATION: f32 = 2.0; // [s]
+
+/// Projectile with linear drag, a = g - k v.
+fn exact(t: f32) -> ([f32; 3], [f32; 3]) {
+    let decay = (-DRAG * t).exp();
+    let terminal = GRAVITY.map(|g| g / DRAG);
+    let position =
+        [0, 1, 2].map(|i| X0[i] + terminal[i] * t + (V0[i] - terminal[i]) * (1.0 - decay) / DRAG);
+    let velocity = [0, 1, 2].map(|i| terminal[i] + (V0[i] - terminal[i]) * decay);
+    (position, velocity)
+}
+
+/// Largest error in position or velocity at the end.
+fn error(integrator: &dyn Integrator, dt: f32) -> f32 {
+    let acceleration = |_: [f32; 3], v: [f32; 3]| [0, 1, 2].map(|i| GRAVITY[i] - DRAG * v[i]);
+    let (mut x, mut v) = (X0, V0);
+    for _ in 0..(DURATION / dt).round() as usize {
+        integrator.step(&mut x, &mut v, &acceleration, dt);
+    }
+    let (ex, ev) = exact(DURATION);
+    (0..3)
+        .map(|i| (x[i] - ex[i]).abs().max((v[i] - ev[i]).abs()))
+        .fold(0.0, f32::max)
+}
+
+/// Observed order of convergence from halving the step twice.
+fn order(integrator: &dyn Integrator) -> f32 {
+    let errors = [0.2, 0.1, 0.05].map(|dt| error(integrator, dt));
+    assert!(errors[2] < errors[1] && errors[1] < errors[0], "{errors:?}");
+    let ratio = (errors[0] / errors[1]).min(errors[1] / errors[2]);
+    ratio.log2()
+}
+
+#[test]
+fn semi_implicit_euler_is_first_order() {
+    let p = order(&SemiImplicitEuler);
+    assert!((0.8..1.5).contains(&p), "order {p}");
+}
+
+#[test]
+fn velocity_verlet_is_second_order() {
+    let p = order(&VelocityVerlet);
+    assert!((1.8..2.5).contains(&p), "order {p}");
+}
+
+#[test]
+fn rk4_is_fourth_order() {
+    let p = order(&Rk4);
+    assert!((3.6..4.5).contains(&p), "order {p}");
+}
+
+#[test]
+fn fast_sparks_take_substeps() {
+    let slow = Particle::new(X0, [0.5, 0.0, 0.0], 1400.0, 60.0);
+    let fast = Particle::new(X0, [50.0, 0.0, 0.0], 1400.0, 60.0);
+    let tiny = Particle::new(X0, [0.5, 0.0, 0.0], 1400.0, 60.0).with_radius(10.0e-6);
+    assert_eq!(slow.substeps(64), 1);
+    assert!(fast.substeps(64) >= 5);
+    assert!(tiny.substeps(64) > 1);
+    assert_eq!(fast.substeps(2), 2);
+}
+
+#[test]
+fn integrator_is_selectable_per_fire() {
+    let run = |physics: Physics| {
+        let mut fire = Fire::with_config(FireConfig {
+            seed: 7,
+            physics,
+            ..FireConfig::default()
+        });
+        for _ in 0..20 {
+            fire.step();
+        }
+        fire.frame(1.0).positions
+    };
+    let euler = run(Physics::default());
+    let rk4 = run(Physics::default().with_integrator(Rk4));
+    assert_eq!(euler.len(), rk4.len());
+    assert_ne!(euler, rk4);
+    assert!(rk4.iter().all(|x| x.is_finite()));
+    // the same choice replays identically
+    assert_eq!(rk4, run(Physics::default().with_integrator(Rk4)));
+}
@@ -0,0 +1,252 @@
+use fire::fire::emitter::Emitter;
+use fire::fire::kernel::{pcg_hash, KernelSimulation, PARTICLE_FLOATS};
+use fire::fire::{Fire, FireConfig, TIME_DELTA};
+use fire::frame::{pack_halves, unpack_halves, INSTANCE_FLOATS};
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::Renderer;
+
+// steady state: the first sparks are long gone
+const WARM_UP: usize = 300;
+const SAMPLES: usize = 100;
+const SAMPLE_STEPS: usize = 10;
+
+/// Mean and standard deviation of every sample.
+#[derive(Debug, Default)]
+struct Moments {
+    count: f64,
+    sum: f64,
+    squares: f64,
+}
+
+impl Moments {
+    fn add(&mut self, x: f32) {
+        self.count += 1.0;
+        self.sum += x as f64;
+        self.squares += (x as f64).powi(2);
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.count
+    }
+
+    fn deviation(&self) -> f64 {
+        (self.squares / self.count - self.mean().powi(2)).sqrt()
+    }
+}
+
+/// Height, speed and temperature of the live sparks, and their count per
+/// sample.
+#[derive(Debug, Default)]
+struct Distribution {
+    height: Moments,
+    speed: Moments,
+    temperature: Moments,
+    count: Moments,
+}
+
+impl Distribution {
+    fn assert_close(&self, other: &Distribution) {
+        let (a, b) = (self.count.mean(), other.count.mean());
+        assert!((a / b).ln().abs() < 0.02, "counts differ: {a} vs {b}");
+
+        let pairs = [
+            ("height", &self.height, &other.height),
+            ("speed", &self.speed, &other.speed),
+            ("temperature", &self.temperature, &other.temperature),
+        ];
+        for (name, a, b) in pairs {
+            let mean = (a.mean() - b.mean()).abs() / b.deviation();
+            assert!(
+                mean < 0.05,
+                "{name} means differ: {} vs {}",
+                a.mean(),
+                b.mean()
+            );
+            let deviation = (a.deviation() / b.deviation()).ln().abs();
+            assert!(
+                deviation < 0.05,
+                "{name} deviations differ: {} vs {}",
+                a.deviation(),
+                b.deviation()
+            );
+        }
+    }
+}
+
+fn config() -> FireConfig {
+    // a pool no spark outlives, so that neither path ever runs out of slots
+    FireConfig {
+        seed: 7,
+        emitter: Emitter::default().with_rate(2000.0),
+        max_particles: 20000,
+        ..FireConfig::default()
+    }
+}
+
+fn cpu_distribution() -> Distribution {
+    let mut fire = Fire::with_config(config());
+    let mut distribution = Distribution::default();
+    for _ in 0..WARM_UP {
+        fire.step();
+    }
+    for _ in 0..SAMPLES {
+        for _ in 0..SAMPLE_STEPS {
+            fire.step();
+        }
+        distribution.count.add(fire.particle_count() as f32);
+        for p in fire.particles() {
+            distribution.height.add(p.position[1]);
+            distribution.speed.add(p.speed());
+            distribution.temperature.add(p.temperature);
+        }
+    }
+    distribution
+}
+
+fn kernel_distribution() -> Distribution {
+    let mut simulation = KernelSimulation::new(&config());
+    let mut distribution = Distribution::default();
+    for _ in 0..WARM_UP {
+        simulation.step();
+    }
+    for _ in 0..SAMPLES {
+        for _ in 0..SAMPLE_STEPS {
+            simulation.step();
+        }
+        distribution.count.add(simulation.particle_count() as f32);
+        for p in simulation.alive() {
+            distribution.height.add(p.position[1]);
+            distribution.speed.add(p.speed());
+            distribution.temperature.add(p.temperature);
+        }
+    }
+    distribution
+}
+
+#[test]
+fn kernel_matches_the_cpu_distribution() {
+    kernel_distribution().assert_close(&cpu_distribution());
+}
+
+#[test]
+fn kernel_renders_like_the_cpu() {
+    // summed light and its mean row, over frames spread across the samples
+    fn brightness(renderer: &SoftwareRenderer, total: &mut (f32, f32)) {
+        for y in 0..renderer.height() {
+            for x in 0..renderer.width() {
+                let [r, g, b, _] = renderer.pixel(x, y);
+                total.0 += r + g + b;
+                total.1 += (r + g + b) * y as f32;
+            }
+        }
+    }
+
+    let mut fire = Fire::with_config(config());
+    let mut simulation = KernelSimulation::new(&config());
+    let mut cpu = SoftwareRenderer::new(128, 128);
+    let mut gpu = SoftwareRenderer::new(128, 128);
+    let (mut cpu_total, mut gpu_total) = ((0.0, 0.0), (0.0, 0.0));
+    for sample in 0..WARM_UP + SAMPLES * SAMPLE_STEPS {
+        fire.step();
+        simulation.step();
+        if sample < WARM_UP || sample % SAMPLE_STEPS != 0 {
+            continue;
+        }
+        cpu.render_instanced(&fire.instances(1.0), &Default::default())
+            .unwrap();
+        gpu.render_simulation(&simulation, &Default::default())
+            .unwrap();
+        brightness(&cpu, &mut cpu_total);
+        brightness(&gpu, &mut gpu_total);
+    }
+
+    let (cpu_row, gpu_row) = (cpu_total.1 / cpu_total.0, gpu_total.1 / gpu_total.0);
+    assert!(cpu_total.0 > 0.0);
+    let ratio = gpu_total.0 / cpu_total.0;
+    assert!(ratio.ln().abs() < 0.05, "light differs by {ratio}");
+    assert!((gpu_row - cpu_row).abs() < 1.0, "{gpu_row} vs {cpu_row}");
+}
+
+#[test]
+fn slots_spawn_at_the_emitter_rate() {
+    let config = FireConfig {
+        max_particles: 1000,
+        ..FireConfig::default()
+    };
+    let mut simulation = KernelSimulation::new(&config);
+    let mut spawned = 0;
+    for _ in 0..100 {
+        let before = simulation.particles.clone();
+        simulation.step();
+        spawned += before
+            .iter()
+            .zip(&simulation.particles)
+            .filter(|(a, b)| b.age < a.age)
+            .count();
+    }
+    // one second
+    let expected = (config.emitter.rate * 100.0 * TIME_DELTA) as usize;
+    assert!(spawned.abs_diff(expected) <= 1, "{spawned} vs {expected}");
+}
+
+#[test]
+fn slots_step_independently() {
+    let mut whole = KernelSimulation::new(&config());
+    for _ in 0..50 {
+        whole.step();
+    }
+
+    // any slot alone, as a GPU thread sees it
+    let mut alone = KernelSimulation::new(&config());
+    for _ in 0..50 {
+        let kernel = alone.kernel.clone();
+        for i in (0..alone.capacity()).rev() {
+            kernel.step(i as u32, &mut alone.particles[i]);
+        }
+        alone.kernel.advance();
+    }
+    assert_eq!(whole.particles, alone.particles);
+    assert!(whole.particle_count() > 0);
+}
+
+#[test]
+fn dead_slots_are_cold_and_not_drawn() {
+    let mut simulation = KernelSimulation::new(&config());
+    for _ in 0..WARM_UP {
+        simulation.step();
+    }
+    let dead = simulation.particles.iter().filter(|p| !p.is_alive());
+    assert!(dead.clone().count() > 0);
+    assert!(dead.clone().all(|p| p.temperature == 0.0));
+    assert_eq!(simulation.instances().len(), simulation.particle_count());
+}
+
+#[test]
+fn slots_upload_instances_first() {
+    let mut simulation = KernelSimulation::new(&config());
+    for _ in 0..10 {
+        simulation.step();
+    }
+    let data = simulation.data();
+    assert_eq!(data.len(), PARTICLE_FLOATS * simulation.capacity());
+    let instances = simulation.instances();
+    let live = data
+        .chunks_exact(PARTICLE_FLOATS)
+        .filter(|slot| slot[6] > 0.0);
+    for (slot, instance) in live.zip(instances.data.chunks_exact(INSTANCE_FLOATS)) {
+        assert_eq!(&slot[..6], &instance[..6]);
+        assert_eq!(
+            unpack_halves(instance[6]),
+            unpack_halves(pack_halves(slot[6], slot[7]))
+        );
+    }
+}
+
+#[test]
+fn hash_is_a_permutation() {
+    // every stage of the hash is invertible, so no two slots share numbers
+    let mut hashes = (0..1 << 16).map(pcg_hash).collect::<Vec<_>>();
+    hashes.sort_unstable();
+    hashes.dedup();
+    assert_eq!(hashes.len(), 1 << 16);
+}
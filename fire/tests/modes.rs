@@ -0,0 +1,154 @@
+mod common;
+
+use common::{lit_pixels, segment, HEIGHT, WIDTH};
+use fire::camera::Camera;
+use fire::fire::Fire;
+use fire::frame::FrameData;
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::{ribbon_offset, sprite_diameter, DrawMode, Renderer};
+
+#[test]
+fn every_segment_becomes_a_quad() {
+    let frame = segment([0.0, 0.0, 0.0], [1.0, 2.0, 0.0], [1.0, 0.5, 0.0, 1.0]);
+    let ribbons = frame.ribbons();
+    assert_eq!(ribbons.vertex_count(), 4);
+    assert_eq!(ribbons.triangles.len(), 6);
+    assert_eq!(ribbons.sides, vec![-1.0, 1.0, -1.0, 1.0]);
+    assert_eq!(ribbons.tangents, [[1.0, 2.0, 0.0]; 4].concat());
+    assert_eq!(&ribbons.positions[6..9], &[1.0, 2.0, 0.0]);
+}
+
+#[test]
+fn ribbons_face_the_camera() {
+    let eye = Camera::default().eye();
+    let (position, tangent) = ([0.5, 0.2, 0.0], [1.0, 0.3, 0.1]);
+    let offset = ribbon_offset(position, tangent, 1.0, 0.1, eye);
+    let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let view = [0, 1, 2].map(|i| eye[i] - position[i]);
+    assert!(dot(offset, tangent).abs() < 1.0e-6);
+    assert!(dot(offset, view).abs() < 1.0e-6);
+    assert!((dot(offset, offset).sqrt() - 0.05).abs() < 1.0e-6);
+
+    let other = ribbon_offset(position, tangent, -1.0, 0.1, eye);
+    assert_eq!(other, offset.map(|x| -x));
+    // seen end on, a segment has no width
+    assert_eq!(
+        ribbon_offset([0.0; 3], [0.0, 0.0, 1.0], 1.0, 0.1, eye),
+        [0.0; 3]
+    );
+}
+
+#[test]
+fn sprites_shrink_with_distance_and_dim_below_a_pixel() {
+    let (near, dim) = sprite_diameter(1.0e-3, 8.0, 1.0, 1600);
+    assert!((near - 12.8).abs() < 1.0e-3, "got {near}");
+    assert_eq!(dim, 1.0);
+    let (far, _) = sprite_diameter(1.0e-3, 8.0, 4.0, 1600);
+    assert!((far - near / 4.0).abs() < 1.0e-3);
+
+    // a quarter pixel wide sprite draws one pixel at a sixteenth of the light
+    let (tiny, dim) = sprite_diameter(1.0e-3, 8.0, 51.2, 1600);
+    assert_eq!(tiny, 1.0);
+    assert!((dim - 1.0 / 16.0).abs() < 1.0e-4);
+}
+
+#[test]
+fn ribbon_width_is_in_meters() {
+    // the view spans [-3, 3] at z = 0, so 0.5 m covers 5.3 rows
+    let frame = segment([-3.0, 0.0, 0.0], [3.0, 0.0, 0.0], [1.0, 0.5, 0.0, 1.0]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_mode(DrawMode::Ribbons { width: 0.5 });
+    renderer.render(&frame).unwrap();
+
+    let rows = (0..HEIGHT)
+        .filter(|&y| renderer.pixel(WIDTH / 2, y)[..3] != [0.0, 0.0, 0.0])
+        .collect::<Vec<_>>();
+    assert_eq!(rows, (HEIGHT / 2 - 3..HEIGHT / 2 + 3).collect::<Vec<_>>());
+    assert!(lit_pixels(&renderer)
+        .iter()
+        .all(|&(_, y)| rows.contains(&y)));
+    assert_eq!(renderer.pixel(WIDTH / 2, HEIGHT / 2), [1.0, 0.5, 0.0, 1.0]);
+}
+
+#[test]
+fn sprites_are_soft_discs() {
+    // radius 0.25 m, scale 1 at 3 m: about 5 px across
+    let frame = FrameData {
+        positions: vec![0.0; 3],
+        colors: vec![1.0; 4],
+        sizes: vec![0.25],
+        ..FrameData::default()
+    };
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_mode(DrawMode::Sprites { scale: 1.0 });
+    renderer.render(&frame).unwrap();
+
+    let lit = lit_pixels(&renderer);
+    assert!(!lit.is_empty());
+    let (cx, cy) = (WIDTH / 2, HEIGHT / 2);
+    assert!(lit
+        .iter()
+        .all(|&(x, y)| x.abs_diff(cx) <= 3 && y.abs_diff(cy) <= 3));
+    let center = renderer.pixel(cx, cy)[0];
+    let edge = renderer.pixel(cx + 2, cy)[0];
+    assert!(center > edge && edge > 0.0, "{center} {edge}");
+    // the center is on the corner between four pixels
+    let (left, right) = (renderer.pixel(cx - 2, cy)[0], renderer.pixel(cx + 1, cy)[0]);
+    assert!((left - right).abs() < 1.0e-5, "{left} {right}");
+}
+
+#[test]
+fn vertices_without_a_size_draw_no_sprite() {
+    let frame = segment([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0; 4]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_mode(DrawMode::sprites());
+    renderer.render(&frame).unwrap();
+    assert!(lit_pixels(&renderer).is_empty());
+}
+
+#[test]
+fn sizes_stay_aligned_with_the_vertices() {
+    let mut frame = FrameData {
+        positions: vec![0.0; 6],
+        colors: vec![1.0; 8],
+        ..FrameData::default()
+    };
+    frame.extend(&FrameData {
+        positions: vec![0.0; 3],
+        colors: vec![1.0; 4],
+        sizes: vec![0.5],
+        ..FrameData::default()
+    });
+    assert_eq!(frame.sizes, vec![0.0, 0.0, 0.5]);
+
+    let mut fire = Fire::new();
+    fire.burst(10);
+    let frame = fire.update();
+    assert_eq!(frame.sizes.len(), frame.vertex_count());
+    assert!(frame.sizes.iter().all(|&r| r > 0.0));
+}
+
+#[test]
+fn modes_parse_by_name() {
+    assert_eq!("lines".parse(), Ok(DrawMode::Lines));
+    assert_eq!("Sprites".parse(), Ok(DrawMode::sprites()));
+    assert_eq!("ribbons".parse(), Ok(DrawMode::ribbons()));
+    assert!("points".parse::<DrawMode>().is_err());
+}
+
+#[test]
+fn modes_upload_only_what_they_draw() {
+    let frame = segment([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0; 4]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    // lines read no sizes
+    renderer.render(&frame).unwrap();
+    assert_eq!(renderer.bytes().frame, 2 * (3 + 4) * 4 + 2 * 2);
+    // and sprites, drawn as points, no indices
+    renderer.set_mode(DrawMode::sprites());
+    renderer.render(&frame).unwrap();
+    assert_eq!(renderer.bytes().frame, 2 * (3 + 4 + 1) * 4);
+    // ribbons upload a quad of positions, colors, tangents and sides
+    renderer.set_mode(DrawMode::ribbons());
+    renderer.render(&frame).unwrap();
+    assert_eq!(renderer.bytes().frame, 4 * (3 + 4 + 3 + 1) * 4 + 6 * 2);
+}
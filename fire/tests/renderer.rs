@@ -0,0 +1,86 @@
+use fire::frame::FrameData;
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::{drawing_buffer_size, grow_capacity, Renderer};
+
+#[test]
+fn capacity_is_kept_while_the_data_fits() {
+    assert_eq!(grow_capacity(1024, 0), 1024);
+    assert_eq!(grow_capacity(1024, 1024), 1024);
+}
+
+#[test]
+fn capacity_grows_geometrically() {
+    assert_eq!(grow_capacity(0, 1), 1);
+    assert_eq!(grow_capacity(0, 1000), 1024);
+    assert_eq!(grow_capacity(1024, 1025), 2048);
+    assert_eq!(grow_capacity(1024, 5000), 8192);
+}
+
+#[test]
+fn rising_counts_reallocate_logarithmically() {
+    let mut capacity = 0;
+    let mut reallocations = 0;
+    for required in 1..=100_000 {
+        let grown = grow_capacity(capacity, required);
+        if grown != capacity {
+            reallocations += 1;
+            capacity = grown;
+        }
+        assert!(capacity >= required);
+    }
+    assert!(reallocations <= 18, "got {reallocations}");
+}
+
+#[test]
+fn drawing_buffer_counts_device_pixels() {
+    assert_eq!(drawing_buffer_size(1280.0, 800.0, 1.0, 8192), (1280, 800));
+    assert_eq!(drawing_buffer_size(1280.0, 800.0, 2.0, 8192), (2560, 1600));
+    // fractional ratios of zoomed pages round to whole pixels
+    assert_eq!(drawing_buffer_size(1001.0, 333.0, 1.5, 8192), (1502, 500));
+}
+
+#[test]
+fn drawing_buffer_fits_the_limit_keeping_its_aspect() {
+    assert_eq!(drawing_buffer_size(3840.0, 2160.0, 3.0, 4096), (4096, 2304));
+    assert_eq!(drawing_buffer_size(1000.0, 4000.0, 2.0, 4096), (1024, 4096));
+}
+
+#[test]
+fn drawing_buffer_is_never_empty() {
+    assert_eq!(drawing_buffer_size(0.0, 0.0, 2.0, 4096), (1, 1));
+    assert_eq!(drawing_buffer_size(800.0, 0.0, 1.0, 4096), (800, 1));
+}
+
+// pixels lit by a cross of two equal arms, across and up
+fn cross_extent(renderer: &mut SoftwareRenderer) -> (usize, usize) {
+    let white = [1.0; 4];
+    renderer
+        .render(&FrameData {
+            positions: vec![-1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0],
+            colors: [white; 4].concat(),
+            segments: vec![0u16, 1, 2, 3].into(),
+            ..FrameData::default()
+        })
+        .unwrap();
+    let lit = |x, y| renderer.pixel(x, y)[0] > 0.5;
+    let (cx, cy) = (renderer.width() / 2, renderer.height() / 2);
+    let across = (0..renderer.width())
+        .filter(|&x| lit(x, cy) || lit(x, cy - 1))
+        .count();
+    let up = (0..renderer.height())
+        .filter(|&y| lit(cx, y) || lit(cx - 1, y))
+        .count();
+    (across, up)
+}
+
+#[test]
+fn resizing_keeps_the_aspect_ratio() {
+    let mut renderer = SoftwareRenderer::new(64, 64);
+    let (across, up) = cross_extent(&mut renderer);
+    assert!(across.abs_diff(up) <= 1, "{across} vs {up}");
+
+    renderer.resize(160, 90);
+    assert_eq!((renderer.width(), renderer.height()), (160, 90));
+    let (across, up) = cross_extent(&mut renderer);
+    assert!(across.abs_diff(up) <= 1, "{across} vs {up}");
+}
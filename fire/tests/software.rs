@@ -0,0 +1,84 @@
+mod common;
+
+use common::{lit_pixels, merge, segment, HEIGHT, WIDTH};
+use fire::frame::FrameData;
+use fire::renderer::software::SoftwareRenderer;
+use fire::renderer::Renderer;
+
+#[test]
+fn horizontal_segment_fills_the_center_row() {
+    // the view spans [-3, 3] at z = 0 with a 90° field of view
+    let frame = segment([-3.0, 0.0, 0.0], [3.0, 0.0, 0.0], [1.0, 0.5, 0.0, 1.0]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.render(&frame).unwrap();
+
+    let expected = (0..WIDTH).map(|x| (x, HEIGHT / 2)).collect::<Vec<_>>();
+    assert_eq!(lit_pixels(&renderer), expected);
+    assert_eq!(renderer.pixel(WIDTH / 2, HEIGHT / 2), [1.0, 0.5, 0.0, 1.0]);
+}
+
+#[test]
+fn vertical_segment_fills_the_center_column() {
+    let frame = segment([0.0, -1.5, 0.0], [0.0, 1.5, 0.0], [1.0, 1.0, 1.0, 1.0]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.render(&frame).unwrap();
+
+    let expected = (HEIGHT / 4..3 * HEIGHT / 4)
+        .map(|y| (WIDTH / 2, y))
+        .collect::<Vec<_>>();
+    assert_eq!(lit_pixels(&renderer), expected);
+}
+
+#[test]
+fn nearer_segment_wins_the_depth_test() {
+    let red = segment([-3.0, 0.0, 0.0], [3.0, 0.0, 0.0], [1.0, 0.0, 0.0, 1.0]);
+    let blue = segment([0.0, -1.0, 1.0], [0.0, 1.0, 1.0], [0.0, 0.0, 1.0, 1.0]);
+
+    // the nearer segment is drawn first and last; it must win both times
+    for frame in [merge(&[blue.clone(), red.clone()]), merge(&[red, blue])] {
+        let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+        renderer.render(&frame).unwrap();
+        assert_eq!(renderer.pixel(WIDTH / 2, HEIGHT / 2), [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(renderer.pixel(WIDTH / 4, HEIGHT / 2), [1.0, 0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn segments_behind_the_camera_are_clipped() {
+    let frame = segment([0.0, 0.0, 4.0], [0.0, 1.0, 5.0], [1.0, 1.0, 1.0, 1.0]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.render(&frame).unwrap();
+    assert!(lit_pixels(&renderer).is_empty());
+}
+
+#[test]
+fn colors_are_interpolated_along_the_segment() {
+    let frame = FrameData {
+        positions: vec![-3.0, 0.0, 0.0, 3.0, 0.0, 0.0],
+        colors: vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0],
+        segments: vec![0u16, 1].into(),
+        ..FrameData::default()
+    };
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.render(&frame).unwrap();
+
+    let left = renderer.pixel(0, HEIGHT / 2);
+    let right = renderer.pixel(WIDTH - 1, HEIGHT / 2);
+    assert!(left[0] > 0.95 && left[2] < 0.05, "got {left:?}");
+    assert!(right[2] > 0.95 && right[0] < 0.05, "got {right:?}");
+}
+
+#[test]
+fn png_has_the_frame_size() {
+    let frame = segment([-3.0, 0.0, 0.0], [3.0, 0.0, 0.0], [1.0, 0.5, 0.0, 1.0]);
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.render(&frame).unwrap();
+
+    let path = std::env::temp_dir().join("fire_software_renderer.png");
+    renderer.save_png(&path).unwrap();
+    let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+    let reader = decoder.read_info().unwrap();
+    assert_eq!(reader.info().width, WIDTH);
+    assert_eq!(reader.info().height, HEIGHT);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,122 @@
+use fire::fire::air;
+use fire::fire::emitter::Emitter;
+use fire::fire::particle::Particle;
+use fire::fire::physics::Physics;
+use fire::fire::thermal::{self, Oxidation};
+use fire::fire::trail::TrailStyle;
+use fire::fire::{Fire, FireConfig};
+use fire::palette::Palette;
+
+fn spark(speed: f32, radius: f32) -> Particle {
+    Particle::new([0.0; 3], [speed, 0.0, 0.0], 1400.0, 60.0).with_radius(radius)
+}
+
+fn temperatures(mut particle: Particle, steps: usize, oxidation: Option<&Oxidation>) -> Vec<f32> {
+    let physics = Physics {
+        oxidation: oxidation.cloned(),
+        ..Physics::default()
+    };
+    (0..steps)
+        .map(|_| {
+            particle.update(&physics);
+            particle.temperature
+        })
+        .collect()
+}
+
+#[test]
+fn radiation_follows_stefan_boltzmann() {
+    let (r, t) = (1.0e-3, 1500.0_f32);
+    let area = 4.0 * std::f32::consts::PI * r * r;
+    let expected = 5.670_374e-8 * area * (t.powi(4) - air::TEMPERATURE.powi(4));
+    let got = thermal::radiation(r, 1.0, t);
+    assert!((got - expected).abs() < 1.0e-6 * expected);
+    assert!((thermal::radiation(r, 0.5, t) - 0.5 * got).abs() < 1.0e-6 * got);
+}
+
+#[test]
+fn sparks_cool_without_oxidation() {
+    let history = temperatures(spark(1.0, 0.5e-3), 100, None);
+    assert!(history.windows(2).all(|w| w[1] < w[0]));
+    assert!(history[99] < 1000.0);
+}
+
+#[test]
+fn fast_and_small_sparks_cool_faster() {
+    let slow = temperatures(spark(1.0, 0.5e-3), 10, None)[9];
+    let fast = temperatures(spark(30.0, 0.5e-3), 10, None)[9];
+    let small = temperatures(spark(1.0, 0.25e-3), 10, None)[9];
+    assert!(fast < slow, "fast: {fast}, slow: {slow}");
+    assert!(small < slow, "small: {small}, slow: {slow}");
+}
+
+#[test]
+fn oxidation_flares_up_late() {
+    let oxidation = Oxidation {
+        delay: 0.3,
+        ..Oxidation::default()
+    };
+    let spark = spark(1.0, 1.0e-3);
+    let plain = temperatures(spark.clone(), 100, None);
+    let burning = temperatures(spark, 100, Some(&oxidation));
+
+    // identical until the fuel ignites
+    assert_eq!(plain[..29], burning[..29]);
+    // then it heats up again
+    let coolest = burning[..40].iter().cloned().fold(f32::MAX, f32::min);
+    let flare = burning[40..].iter().cloned().fold(f32::MIN, f32::max);
+    assert!(flare > coolest + 50.0, "coolest: {coolest}, flare: {flare}");
+}
+
+#[test]
+fn color_comes_from_temperature_only() {
+    let mut slow = spark(1.0, 0.5e-3);
+    let mut fast = spark(40.0, 0.5e-3);
+    slow.update(&Physics::default());
+    fast.update(&Physics::default());
+    fast.temperature = slow.temperature;
+    slow.update(&Physics::default());
+    fast.update(&Physics::default());
+    assert!(fast.temperature < slow.temperature);
+
+    let palette = Palette::black_body();
+    let style = TrailStyle::default();
+    for particle in [&slow, &fast] {
+        let color = palette.color(particle.temperature);
+        let brightness = palette.brightness(particle.temperature);
+        let got = style.color(particle.temperature, 0);
+        for (got, expected) in got.iter().zip(color) {
+            assert!((got - brightness * expected).abs() < 1.0e-6);
+        }
+    }
+
+    // and the heads of the frame are drawn in it
+    let mut fire = Fire::new();
+    fire.burst(20);
+    fire.step();
+    let frame = fire.frame(1.0);
+    let n = fire.particle_count();
+    for (i, particle) in fire.particles().iter().enumerate() {
+        let head = &frame.colors[4 * (n + i)..4 * (n + i + 1)];
+        assert_eq!(head, style.color(particle.temperature, 0));
+    }
+}
+
+#[test]
+fn cooled_sparks_fade_out_and_despawn() {
+    let palette = Palette::black_body();
+    assert!(palette.brightness(800.0) < 0.05);
+    assert!(palette.brightness(1500.0) > 0.99 && palette.brightness(1500.0) < 1.01);
+
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new([0.0, 0.5, 0.0])
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0),
+        ..FireConfig::default()
+    });
+    fire.burst(100);
+    for _ in 0..300 {
+        fire.step();
+    }
+    assert_eq!(fire.particle_count(), 0);
+}
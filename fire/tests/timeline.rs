@@ -0,0 +1,104 @@
+use fire::fire::timeline::{Event, Phase, Timeline};
+use fire::fire::{Fire, FireConfig, TIME_DELTA};
+
+const PHASES: [Phase; 5] = [
+    Phase::Tsubomi,
+    Phase::Botan,
+    Phase::Matsuba,
+    Phase::Yanagi,
+    Phase::ChiriGiku,
+];
+
+#[test]
+fn senko_hanabi_lasts_about_a_minute() {
+    let timeline = Timeline::senko_hanabi();
+    assert_eq!(timeline.duration(), 60.0);
+    let phases = timeline
+        .stages()
+        .iter()
+        .map(|s| s.phase)
+        .collect::<Vec<_>>();
+    assert_eq!(phases, PHASES);
+}
+
+#[test]
+fn events_report_every_transition_in_order() {
+    let mut timeline = Timeline::senko_hanabi();
+    let mut events = vec![];
+    while !timeline.is_finished() {
+        events.extend(timeline.advance(0.25));
+    }
+
+    let mut expected = vec![];
+    let mut start = 0.0;
+    for stage in timeline.stages() {
+        expected.push(Event::PhaseStarted {
+            phase: stage.phase,
+            time: start,
+        });
+        start += stage.duration;
+    }
+    expected.push(Event::Finished { time: start });
+    assert_eq!(events, expected);
+
+    // nothing more once finished
+    assert!(timeline.advance(1.0).is_empty());
+    assert_eq!(timeline.phase(), None);
+}
+
+#[test]
+fn a_long_step_reports_the_phases_it_skips() {
+    let mut timeline = Timeline::senko_hanabi();
+    let events = timeline.advance(20.0);
+    let phases = events
+        .iter()
+        .map(|e| match e {
+            Event::PhaseStarted { phase, .. } => *phase,
+            Event::Finished { .. } => panic!("finished too early"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(phases, PHASES[..3]);
+    assert_eq!(timeline.phase(), Some(Phase::Matsuba));
+}
+
+#[test]
+fn scaled_timeline_keeps_the_proportions() {
+    let original = Timeline::senko_hanabi();
+    let scaled = Timeline::senko_hanabi().scaled(6.0);
+    assert!((scaled.duration() - 6.0).abs() < 1.0e-4);
+    for (a, b) in original.stages().iter().zip(scaled.stages()) {
+        assert!((b.duration - a.duration / 10.0).abs() < 1.0e-4);
+        assert_eq!(a.params, b.params);
+    }
+}
+
+#[test]
+fn fire_follows_the_phases_and_burns_out() {
+    let mut fire = Fire::with_config(FireConfig {
+        timeline: Some(Timeline::senko_hanabi().scaled(3.0)),
+        ..FireConfig::default()
+    });
+    let steps = (3.0 / TIME_DELTA) as usize;
+
+    let mut phases = vec![];
+    let mut rates = vec![];
+    for _ in 0..steps + 10 {
+        fire.step();
+        for event in fire.drain_events().collect::<Vec<_>>() {
+            if let Event::PhaseStarted { phase, .. } = event {
+                phases.push(phase);
+                rates.push(fire.timeline().unwrap().params().unwrap().rate);
+            }
+        }
+    }
+    assert_eq!(phases, PHASES);
+    // the bud barely sparks, the pine needles the most
+    assert!(rates[0] < rates[1] && rates[1] < rates[2]);
+    assert!(fire.timeline().unwrap().is_finished());
+
+    // no new sparks once burnt out
+    for _ in 0..300 {
+        fire.step();
+    }
+    assert_eq!(fire.particle_count(), 0);
+}
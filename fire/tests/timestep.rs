@@ -0,0 +1,87 @@
+use fire::fire::{Fire, FireConfig, TIME_DELTA};
+use fire::timestep::FixedTimestep;
+
+/// Steps run for one second of frames at the refresh rate.
+fn steps_in_a_second(clock: &mut FixedTimestep, hz: u32) -> u32 {
+    (0..=hz)
+        .map(|i| clock.advance(1000.0 * i as f64 / hz as f64))
+        .sum()
+}
+
+#[test]
+fn refresh_rate_does_not_change_the_speed() {
+    for hz in [30, 60, 120, 144] {
+        let mut clock = FixedTimestep::new(TIME_DELTA);
+        let steps = steps_in_a_second(&mut clock, hz);
+        assert!((99..=100).contains(&steps), "{hz} Hz ran {steps} steps");
+    }
+}
+
+#[test]
+fn substeps_are_capped_after_a_pause() {
+    let mut clock = FixedTimestep::new(TIME_DELTA).with_max_substeps(4);
+    clock.advance(0.0);
+    // a background tab resumes after 5 s
+    assert_eq!(clock.advance(5000.0), 4);
+    assert!(clock.alpha() < 1.0);
+    assert_eq!(clock.advance(5010.0), 1);
+}
+
+#[test]
+fn time_scale_slows_the_simulation() {
+    let mut clock = FixedTimestep::new(TIME_DELTA).with_time_scale(0.25);
+    let steps = steps_in_a_second(&mut clock, 60);
+    assert!((24..=25).contains(&steps), "ran {steps} steps");
+}
+
+#[test]
+fn alpha_is_the_leftover_fraction_of_a_step() {
+    let mut clock = FixedTimestep::new(TIME_DELTA);
+    clock.advance(0.0);
+    assert_eq!(clock.advance(25.0), 2);
+    assert!(
+        (clock.alpha() - 0.5).abs() < 1.0e-3,
+        "got {}",
+        clock.alpha()
+    );
+}
+
+#[test]
+fn interpolated_frames_span_the_last_two_states() {
+    let mut fire = Fire::with_config(FireConfig {
+        seed: 3,
+        ..FireConfig::default()
+    });
+    fire.step();
+    fire.step();
+    let end = fire.frame(1.0);
+    let middle = fire.frame(0.5);
+    let start = fire.frame(0.0);
+
+    // the head at alpha 0 is where the tail is at alpha 1
+    let n = end.vertex_count() / 2;
+    assert_eq!(start.positions[3 * n..], end.positions[..3 * n]);
+    for i in 0..end.positions.len() {
+        let expected = (start.positions[i] + end.positions[i]) / 2.0;
+        assert!((middle.positions[i] - expected).abs() < 1.0e-4);
+    }
+}
+
+#[test]
+fn same_wall_time_gives_the_same_state_at_any_refresh_rate() {
+    let run = |hz: u32| {
+        let mut fire = Fire::with_config(FireConfig {
+            seed: 9,
+            ..FireConfig::default()
+        });
+        let mut clock = FixedTimestep::new(TIME_DELTA);
+        // half a second, aligned to whole steps at both rates
+        for i in 0..=hz / 2 {
+            for _ in 0..clock.advance(1000.0 * i as f64 / hz as f64) {
+                fire.step();
+            }
+        }
+        fire.frame(1.0)
+    };
+    assert_eq!(run(50), run(100));
+}
@@ -0,0 +1,114 @@
+use fire::fire::emitter::Emitter;
+use fire::fire::trail::{Trail, TrailPoint, TrailStyle};
+use fire::fire::{Fire, FireConfig};
+
+const EMITTER: [f32; 3] = [0.0, 0.5, 0.0];
+
+fn point(x: f32) -> TrailPoint {
+    TrailPoint {
+        position: [x, 0.0, 0.0],
+        temperature: 1000.0 + x,
+    }
+}
+
+#[test]
+fn ring_buffer_keeps_the_newest_points() {
+    let mut trail = Trail::new(point(0.0), 3);
+    assert_eq!(trail.iter().collect::<Vec<_>>(), [point(0.0); 3]);
+    for x in 1..=5 {
+        trail.push(point(x as f32));
+    }
+    assert_eq!(trail.capacity(), 3);
+    assert_eq!(
+        trail.iter().collect::<Vec<_>>(),
+        [point(5.0), point(4.0), point(3.0)]
+    );
+    // beyond the capacity is the oldest point
+    assert_eq!(trail.get(10), point(3.0));
+}
+
+fn streaking(length: usize) -> Fire {
+    let mut fire = Fire::with_config(FireConfig {
+        emitter: Emitter::new(EMITTER)
+            .with_rate(0.0)
+            .with_lifetime(60.0, 60.0)
+            .with_speed(5.0, 10.0)
+            .with_spark_radius(2.0e-3, 2.0e-3),
+        trail: TrailStyle::streaks(length),
+        ..FireConfig::default()
+    });
+    fire.burst(20);
+    fire
+}
+
+#[test]
+fn trails_are_strips_through_the_past_positions() {
+    let length = 8;
+    let mut fire = streaking(length);
+    let mut heads = vec![];
+    for _ in 0..20 {
+        let frame = fire.update();
+        let n = fire.particle_count();
+        heads.push(frame.positions[3 * n * length..3 * n * (length + 1)].to_vec());
+    }
+    let frame = fire.frame(1.0);
+    let n = fire.particle_count();
+    assert_eq!(frame.vertex_count(), n * (length + 1));
+    assert_eq!(frame.segment_count(), n * length);
+
+    let segments = frame.segments.iter().collect::<Vec<_>>();
+    for (i, strip) in segments.chunks(2 * length).enumerate() {
+        for (block, segment) in strip.chunks(2).enumerate() {
+            assert_eq!(
+                segment,
+                [(block * n + i) as u32, ((block + 1) * n + i) as u32]
+            );
+        }
+    }
+    // block k from the head is where the heads were k steps ago
+    for k in 0..=length {
+        let block = length - k;
+        let positions = &frame.positions[3 * n * block..3 * n * (block + 1)];
+        assert_eq!(positions, &heads[heads.len() - 1 - k][..]);
+    }
+}
+
+#[test]
+fn trails_fade_and_cool_towards_the_end() {
+    let length = 8;
+    let mut fire = streaking(length);
+    for _ in 0..20 {
+        fire.step();
+    }
+    let frame = fire.frame(1.0);
+    let n = fire.particle_count();
+    let color = |block: usize, i: usize| {
+        let c = &frame.colors[4 * (block * n + i)..4 * (block * n + i) + 4];
+        [c[0], c[1], c[2]]
+    };
+    for i in 0..n {
+        let head = color(length, i);
+        let middle = color(length / 2, i);
+        let end = color(0, i);
+        // dimmer
+        assert!(middle[0] < head[0] && end[0] <= middle[0]);
+        assert_eq!(end, [0.0; 3]);
+        // redder
+        assert!(middle[1] / middle[0] < head[1] / head[0]);
+    }
+}
+
+#[test]
+fn single_segment_trails_keep_the_dash_layout() {
+    let mut fire = Fire::new();
+    for _ in 0..5 {
+        fire.step();
+    }
+    let frame = fire.frame(0.5);
+    let n = fire.particle_count();
+    assert_eq!(frame.vertex_count(), 2 * n);
+    let segments = frame.segments.iter().collect::<Vec<_>>();
+    for (i, segment) in segments.chunks(2).enumerate() {
+        assert_eq!(segment, [i as u32, (i + n) as u32]);
+    }
+}